@@ -0,0 +1,69 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use mlc::mlc::limit::Limits;
+
+// simple linear congruential generator to stay deterministic without dependencies, a copy of the
+// one used by the tests
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, modulus: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % modulus
+    }
+}
+
+fn filled_limits(n_categories: usize, n_updates: usize, rng: &mut Lcg) -> Limits<usize> {
+    let mut limits = Limits::new(2);
+    for category in 0..n_categories {
+        limits.add_category(category);
+    }
+    for _ in 0..n_updates {
+        let category = rng.next(n_categories as u64) as usize;
+        limits.update_limit(category, &[rng.next(10_000), rng.next(10_000)]);
+    }
+    limits
+}
+
+fn bench_is_limit_exceeded(c: &mut Criterion) {
+    let mut group = c.benchmark_group("is_limit_exceeded");
+    for n_categories in [100, 1_000, 5_000] {
+        let mut rng = Lcg(42);
+        let limits = filled_limits(n_categories, n_categories * 20, &mut rng);
+        let queries = (0..1_000)
+            .map(|_| [rng.next(10_000), rng.next(10_000)])
+            .collect::<Vec<_>>();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(n_categories),
+            &queries,
+            |b, queries| {
+                b.iter(|| {
+                    for query in queries {
+                        black_box(limits.is_limit_exceeded(query));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_update_limit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_limit");
+    group.sample_size(10);
+    for n_categories in [100, 1_000, 5_000] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(n_categories),
+            &n_categories,
+            |b, &n_categories| {
+                b.iter(|| filled_limits(n_categories, n_categories * 20, &mut Lcg(42)))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_is_limit_exceeded, bench_update_limit);
+criterion_main!(benches);
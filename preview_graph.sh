@@ -1,11 +1,15 @@
 #!/bin/bash
 
-DOT_FILENAME="data/graph.dot"
+# usage: ./preview_graph.sh <edges.csv> [<start node> <target node>]
+# Renders the graph, and the Pareto paths from start to target if given, to data/graph.svg.
+# Requires Graphviz.
+
 SVG_FILENAME="data/graph.svg"
 
-inotifywait -m -e modify $DOT_FILENAME | while read -r directory event filename; do
-    echo "Regenerating svg"
-    dot -Tsvg $DOT_FILENAME -o $SVG_FILENAME
-    # killall feh
-    # feh $SVG_FILENAME &
-done
+if [ $# -ne 1 ] && [ $# -ne 3 ]; then
+    echo "usage: $0 <edges.csv> [<start node> <target node>]" >&2
+    exit 1
+fi
+
+mkdir -p "$(dirname $SVG_FILENAME)"
+cargo run --release -- render "$1" $SVG_FILENAME "${@:2}"
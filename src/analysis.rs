@@ -0,0 +1,277 @@
+mod test;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::bag::{Bag, Weight};
+use crate::mlc::Bags;
+
+/// Quality measures of the Pareto front of a single node, see `analyze`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontMetrics {
+    /// Number of distinct non-dominated value vectors.
+    pub size: usize,
+    /// Volume dominated by the front and bounded by the reference point, see `hypervolume`.
+    pub hypervolume: f64,
+    /// Best value of each criterion over the front.
+    pub ideal: Vec<Weight>,
+    /// Worst value of each criterion over the front.
+    pub nadir: Vec<Weight>,
+    /// See `knee_points`.
+    pub knee_points: Vec<Vec<Weight>>,
+}
+
+/// Distance of an approximated front to an exact one, see `compare`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontComparison {
+    /// See `additive_epsilon`. `None` if the node was not reached by the approximation.
+    pub additive_epsilon: Option<f64>,
+    /// See `igd`. `None` if the node was not reached by the approximation.
+    pub igd: Option<f64>,
+}
+
+/// Returns the distinct non-dominated value vectors of the bag, sorted lexicographically.
+///
+/// Labels are compared by their values only, so labels that differ in their automaton state or
+/// line are pooled. Use `MLC::accepted_bags` to analyze runs with an automaton.
+pub fn pareto_front<T: Eq + Hash>(bag: &Bag<T>) -> Vec<Vec<Weight>> {
+    let mut values = bag
+        .labels
+        .iter()
+        .map(|label| label.values.clone())
+        .collect::<Vec<_>>();
+    values.sort();
+    values.dedup();
+    values
+        .iter()
+        .filter(|a| !values.iter().any(|b| b != *a && weakly_dominates(b, a)))
+        .cloned()
+        .collect()
+}
+
+fn weakly_dominates(a: &[Weight], b: &[Weight]) -> bool {
+    a.iter().zip(b.iter()).all(|(a, b)| a <= b)
+}
+
+/// Returns the metrics of the front of every node, with the hypervolume relative to `reference`.
+/// Returns an error if a label does not have one value per value of `reference`.
+pub fn analyze<T: Eq + Hash + Clone>(
+    bags: &Bags<T>,
+    reference: &[Weight],
+) -> Result<HashMap<T, FrontMetrics>, String> {
+    bags.iter()
+        .map(|(node_id, bag)| {
+            let front = pareto_front(bag);
+            let metrics = FrontMetrics {
+                size: front.len(),
+                hypervolume: hypervolume(&front, reference)?,
+                ideal: ideal_point(&front).unwrap_or_default(),
+                nadir: nadir_point(&front).unwrap_or_default(),
+                knee_points: knee_points(&front),
+            };
+            Ok((node_id.clone(), metrics))
+        })
+        .collect()
+}
+
+/// Compares the fronts of an approximate run to those of an exact run, for every node reached by
+/// the exact run. Returns an error if the labels of the runs have different numbers of values.
+pub fn compare<T: Eq + Hash + Clone>(
+    approximation: &Bags<T>,
+    exact: &Bags<T>,
+) -> Result<HashMap<T, FrontComparison>, String> {
+    exact
+        .iter()
+        .map(|(node_id, bag)| {
+            let reference = pareto_front(bag);
+            let front = approximation.get(node_id).map(pareto_front);
+            let comparison = match front {
+                Some(front) => FrontComparison {
+                    additive_epsilon: additive_epsilon(&front, &reference)?,
+                    igd: igd(&front, &reference)?,
+                },
+                None => FrontComparison {
+                    additive_epsilon: None,
+                    igd: None,
+                },
+            };
+            Ok((node_id.clone(), comparison))
+        })
+        .collect()
+}
+
+/// Returns the volume of the region that is weakly dominated by some point of the front and
+/// strictly dominates `reference`, i.e. the hypervolume indicator for minimization. Points that
+/// are not better than `reference` in every criterion do not contribute.
+///
+/// The front does not need to be free of dominated points. The running time grows with
+/// `front.len()` to the power of the number of criteria, which is fine for the fronts of a few
+/// criteria that MLC produces.
+///
+/// Returns an error if a point does not have one value per value of `reference`.
+pub fn hypervolume(front: &[Vec<Weight>], reference: &[Weight]) -> Result<f64, String> {
+    check_dimension(front, reference.len())?;
+    let points = front
+        .iter()
+        .filter(|point| point.iter().zip(reference).all(|(p, r)| p < r))
+        .map(|point| point.as_slice())
+        .collect::<Vec<_>>();
+    if reference.is_empty() {
+        return Ok(0.0);
+    }
+    Ok(hypervolume_of(points, reference))
+}
+
+// the metrics compare points value by value, so all points must have the same length
+fn check_dimension(points: &[Vec<Weight>], dimension: usize) -> Result<(), String> {
+    match points.iter().find(|point| point.len() != dimension) {
+        Some(point) => Err(format!(
+            "expected points with {} values, found {:?}",
+            dimension, point
+        )),
+        None => Ok(()),
+    }
+}
+
+// slices the volume along the last criterion and recurses on the remaining ones
+fn hypervolume_of(mut points: Vec<&[Weight]>, reference: &[Weight]) -> f64 {
+    let last = reference.len() - 1;
+    if points.is_empty() {
+        return 0.0;
+    }
+    if last == 0 {
+        let best = points.iter().map(|point| point[0]).min().unwrap();
+        return (reference[0] - best) as f64;
+    }
+    points.sort_by_key(|point| point[last]);
+    let mut volume = 0.0;
+    for i in 0..points.len() {
+        let upper = points
+            .get(i + 1)
+            .map_or(reference[last], |point| point[last]);
+        let height = upper - points[i][last];
+        if height == 0 {
+            continue;
+        }
+        let base = points[..=i].iter().map(|point| &point[..last]).collect();
+        volume += height as f64 * hypervolume_of(base, &reference[..last]);
+    }
+    volume
+}
+
+/// Returns the best value of each criterion over the front, or `None` if it is empty.
+pub fn ideal_point(front: &[Vec<Weight>]) -> Option<Vec<Weight>> {
+    componentwise(front, |a, b| a.min(b))
+}
+
+/// Returns the worst value of each criterion over the front, or `None` if it is empty. Points
+/// of the front are expected to be non-dominated, as for the fronts of `pareto_front`.
+pub fn nadir_point(front: &[Vec<Weight>]) -> Option<Vec<Weight>> {
+    componentwise(front, |a, b| a.max(b))
+}
+
+fn componentwise(
+    front: &[Vec<Weight>],
+    f: impl Fn(Weight, Weight) -> Weight,
+) -> Option<Vec<Weight>> {
+    let (first, rest) = front.split_first()?;
+    Some(rest.iter().fold(first.clone(), |acc, point| {
+        acc.iter().zip(point).map(|(a, b)| f(*a, *b)).collect()
+    }))
+}
+
+/// Returns the knee points of the front, i.e. the points with the best trade-off between the
+/// criteria.
+///
+/// The criteria are normalized to [0, 1] between the ideal and the nadir point, and the knee
+/// points are those with the smallest sum of normalized values. For two criteria, these are the
+/// points farthest below the line through the two extreme points. Criteria that are equal on
+/// the whole front are ignored. Several points are returned if they are tied.
+pub fn knee_points(front: &[Vec<Weight>]) -> Vec<Vec<Weight>> {
+    let (Some(ideal), Some(nadir)) = (ideal_point(front), nadir_point(front)) else {
+        return vec![];
+    };
+    let score = |point: &Vec<Weight>| -> f64 {
+        point
+            .iter()
+            .zip(ideal.iter().zip(nadir.iter()))
+            .filter(|(_, (ideal, nadir))| nadir > ideal)
+            .map(|(value, (ideal, nadir))| (value - ideal) as f64 / (nadir - ideal) as f64)
+            .sum()
+    };
+    let scores = front.iter().map(score).collect::<Vec<_>>();
+    let best = scores.iter().copied().fold(f64::INFINITY, f64::min);
+    front
+        .iter()
+        .zip(scores)
+        .filter(|(_, score)| *score <= best + 1e-9)
+        .map(|(point, _)| point.clone())
+        .collect()
+}
+
+/// Returns the additive epsilon indicator of the front relative to the reference front, i.e. the
+/// smallest value that, subtracted from every criterion of every point of `front`, makes each
+/// point of `reference` weakly dominated by some point of `front`.
+///
+/// It is 0 if both fronts are equal and positive if `front` misses or worsens points of
+/// `reference`. Returns `None` if either front is empty, and an error if the points do not all
+/// have the same number of values.
+pub fn additive_epsilon(
+    front: &[Vec<Weight>],
+    reference: &[Vec<Weight>],
+) -> Result<Option<f64>, String> {
+    check_fronts(front, reference)?;
+    if front.is_empty() {
+        return Ok(None);
+    }
+    Ok(reference
+        .iter()
+        .map(|r| {
+            front
+                .iter()
+                .map(|a| {
+                    a.iter()
+                        .zip(r)
+                        .map(|(a, r)| *a as f64 - *r as f64)
+                        .fold(f64::NEG_INFINITY, f64::max)
+                })
+                .fold(f64::INFINITY, f64::min)
+        })
+        .reduce(f64::max))
+}
+
+fn check_fronts(front: &[Vec<Weight>], reference: &[Vec<Weight>]) -> Result<(), String> {
+    if let Some(point) = reference.first().or(front.first()) {
+        check_dimension(front, point.len())?;
+        check_dimension(reference, point.len())?;
+    }
+    Ok(())
+}
+
+/// Returns the inverted generational distance of the front relative to the reference front, i.e.
+/// the mean Euclidean distance of the points of `reference` to their closest point of `front`.
+///
+/// It is 0 if `front` contains every point of `reference`. Returns `None` if either front is
+/// empty, and an error if the points do not all have the same number of values.
+pub fn igd(front: &[Vec<Weight>], reference: &[Vec<Weight>]) -> Result<Option<f64>, String> {
+    check_fronts(front, reference)?;
+    if front.is_empty() || reference.is_empty() {
+        return Ok(None);
+    }
+    let total = reference
+        .iter()
+        .map(|r| {
+            front
+                .iter()
+                .map(|a| {
+                    a.iter()
+                        .zip(r)
+                        .map(|(a, r)| (*a as f64 - *r as f64).powi(2))
+                        .sum::<f64>()
+                        .sqrt()
+                })
+                .fold(f64::INFINITY, f64::min)
+        })
+        .sum::<f64>();
+    Ok(Some(total / reference.len() as f64))
+}
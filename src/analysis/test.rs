@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::analysis::*;
+    use crate::bag::{Bag, Label};
+    use crate::mlc::{Bags, MLC};
+    use crate::read;
+
+    fn bag(values: &[&[u64]]) -> Bag<usize> {
+        Bag {
+            labels: values
+                .iter()
+                .enumerate()
+                .map(|(state, values)| Label {
+                    values: values.to_vec(),
+                    hidden_values: vec![],
+                    path: vec![],
+                    node_id: 0,
+                    // distinct states keep equal values apart
+                    state,
+                    line: None,
+                    edge: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_pareto_front() {
+        let front = pareto_front(&bag(&[&[3, 1], &[1, 3], &[2, 2], &[2, 3], &[1, 3]]));
+        assert_eq!(front, vec![vec![1, 3], vec![2, 2], vec![3, 1]]);
+    }
+
+    #[test]
+    fn test_hypervolume() {
+        let front = vec![vec![1, 3], vec![2, 2], vec![3, 1]];
+        assert_eq!(hypervolume(&front, &[4, 4]).unwrap(), 6.0);
+        // dominated points and points outside the reference do not change the volume
+        let mut with_dominated = front.clone();
+        with_dominated.extend([vec![3, 3], vec![0, 5]]);
+        assert_eq!(hypervolume(&with_dominated, &[4, 4]).unwrap(), 6.0);
+        assert_eq!(hypervolume(&front, &[1, 4]).unwrap(), 0.0);
+
+        assert_eq!(hypervolume(&[vec![1, 1, 1]], &[2, 3, 4]).unwrap(), 6.0);
+        assert_eq!(
+            hypervolume(&[vec![0, 0, 1], vec![1, 1, 0]], &[2, 2, 2]).unwrap(),
+            5.0
+        );
+        assert_eq!(hypervolume(&[], &[2, 2]).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_ideal_nadir_and_knee_points() {
+        let front = vec![vec![0, 10], vec![2, 3], vec![10, 0]];
+        assert_eq!(ideal_point(&front), Some(vec![0, 0]));
+        assert_eq!(nadir_point(&front), Some(vec![10, 10]));
+        assert_eq!(knee_points(&front), vec![vec![2, 3]]);
+        // ties are all returned
+        let front = vec![vec![0, 2], vec![1, 1], vec![2, 0]];
+        assert_eq!(knee_points(&front), front);
+        assert_eq!(ideal_point(&[]), None);
+        assert!(knee_points(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_distance_metrics() {
+        let exact = vec![vec![1, 3], vec![2, 2], vec![3, 1]];
+        let approximation = vec![vec![1, 3], vec![3, 1]];
+        assert_eq!(additive_epsilon(&exact, &exact).unwrap(), Some(0.0));
+        assert_eq!(additive_epsilon(&approximation, &exact).unwrap(), Some(1.0));
+        // a better front has a negative epsilon
+        assert_eq!(additive_epsilon(&[vec![0, 0]], &exact).unwrap(), Some(-1.0));
+        assert_eq!(igd(&exact, &exact).unwrap(), Some(0.0));
+        assert_eq!(
+            igd(&approximation, &exact).unwrap(),
+            Some(2f64.sqrt() / 3.0)
+        );
+        assert_eq!(additive_epsilon(&[], &exact).unwrap(), None);
+        assert_eq!(igd(&approximation, &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_mismatched_lengths() {
+        let front = vec![vec![1, 3], vec![2]];
+        assert!(hypervolume(&front, &[4, 4]).is_err());
+        assert!(hypervolume(&[vec![1, 1, 1]], &[4, 4]).is_err());
+        assert!(additive_epsilon(&front, &[vec![1, 3]]).is_err());
+        assert!(additive_epsilon(&[vec![1, 3]], &[vec![1, 3, 0]]).is_err());
+        assert!(igd(&[vec![1, 3]], &front).is_err());
+        assert!(igd(&[vec![1]], &[]).is_ok());
+
+        let bags: Bags<usize> = HashMap::from([(0, bag(&[&[1, 3]]))]);
+        assert!(analyze(&bags, &[4, 4, 4]).is_err());
+        let other: Bags<usize> = HashMap::from([(0, bag(&[&[1, 3, 0]]))]);
+        assert!(compare(&other, &bags).is_err());
+    }
+
+    #[test]
+    fn test_analyze_and_compare() {
+        let g = read::read_graph_with_int_ids("testdata/edges.csv").unwrap();
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap().clone();
+
+        let metrics = analyze(&bags, &[16, 16]).unwrap();
+        assert_eq!(metrics.len(), 5);
+        // every sum of 1, 2, 4 and 8 splits 15 into two criteria
+        assert_eq!(metrics[&4].size, 16);
+        assert_eq!(metrics[&4].ideal, vec![0, 0]);
+        assert_eq!(metrics[&4].nadir, vec![15, 15]);
+        assert_eq!(metrics[&0].size, 1);
+        assert_eq!(metrics[&0].hypervolume, 256.0);
+
+        let comparison = compare(&bags, &bags).unwrap();
+        assert!(comparison.values().all(|c| c.additive_epsilon == Some(0.0)));
+        assert!(comparison.values().all(|c| c.igd == Some(0.0)));
+
+        // an approximation that only reached the start node
+        let approximation: Bags<usize> = HashMap::from([(0, bags[&0].clone())]);
+        let comparison = compare(&approximation, &bags).unwrap();
+        assert_eq!(comparison[&0].additive_epsilon, Some(0.0));
+        assert_eq!(comparison[&4].additive_epsilon, None);
+    }
+}
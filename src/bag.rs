@@ -4,6 +4,7 @@ use petgraph::graph::EdgeReference;
 use petgraph::visit::EdgeRef;
 use std::cmp::Ordering;
 use std::collections::HashSet;
+use std::fmt;
 use std::hash::Hash;
 
 pub type Weight = u64;
@@ -19,18 +20,131 @@ impl From<Vec<u64>> for Weights {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct WeightsTuple {
     pub weights: Vec<Weight>,
     pub hidden_weights: Vec<Weight>,
+    /// Bitmask of edge attributes, e.g. the mode or accessibility, used to filter edges per
+    /// query.
+    pub attributes: u64,
+    /// Label of the edge, e.g. its mode, read by the automaton of regular-language constrained
+    /// queries.
+    pub mode: u32,
+    /// Line of the edge, e.g. a transit route. Changing lines costs `transfer_weights` and
+    /// `wait_weights`.
+    pub line: u32,
+    /// Added to the weights when the edge is entered from an edge of another line.
+    /// Empty if there is no transfer penalty. Costs of specific pairs of edges, e.g. turns, are
+    /// given by `mlc::turns::TurnCosts`.
+    pub transfer_weights: Vec<Weight>,
+    /// Cost of waiting at the source node for the line of the edge, added to the weights when
+    /// the edge is entered from an edge of another line, i.e. on a transfer. Staying on the line
+    /// and leaving the start of the search cost nothing. Empty if there is no waiting cost.
+    pub wait_weights: Vec<Weight>,
+}
+
+impl WeightsTuple {
+    pub fn as_edge_weights(&self) -> EdgeWeights<'_> {
+        EdgeWeights {
+            weights: &self.weights,
+            hidden_weights: &self.hidden_weights,
+            attributes: self.attributes,
+            mode: self.mode,
+            line: self.line,
+            transfer_weights: &self.transfer_weights,
+            wait_weights: &self.wait_weights,
+        }
+    }
+}
+
+/// A borrowed view of the weights of an edge, see `WeightsTuple` for the fields. Graph backends
+/// that do not store a `WeightsTuple` per edge hand out their edges as this view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeWeights<'a> {
+    pub weights: &'a [Weight],
+    pub hidden_weights: &'a [Weight],
+    pub attributes: u64,
+    pub mode: u32,
+    pub line: u32,
+    pub transfer_weights: &'a [Weight],
+    pub wait_weights: &'a [Weight],
+}
+
+impl EdgeWeights<'_> {
+    pub fn to_weights_tuple(&self) -> WeightsTuple {
+        WeightsTuple {
+            weights: self.weights.to_vec(),
+            hidden_weights: self.hidden_weights.to_vec(),
+            attributes: self.attributes,
+            mode: self.mode,
+            line: self.line,
+            transfer_weights: self.transfer_weights.to_vec(),
+            wait_weights: self.wait_weights.to_vec(),
+        }
+    }
+}
+
+/// Selects a single criterion of a label, either one of its values or one of its hidden values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Criterion {
+    Value(usize),
+    HiddenValue(usize),
+}
+
+impl Criterion {
+    /// Returns true if the criterion exists for labels with the given number of values and
+    /// hidden values.
+    pub fn is_valid(&self, n_values: usize, n_hidden_values: usize) -> bool {
+        match *self {
+            Criterion::Value(index) => index < n_values,
+            Criterion::HiddenValue(index) => index < n_hidden_values,
+        }
+    }
+}
+
+impl fmt::Display for Criterion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Criterion::Value(index) => write!(f, "value {}", index),
+            Criterion::HiddenValue(index) => write!(f, "hidden value {}", index),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Label<T> {
     pub values: Vec<u64>,
     pub hidden_values: Vec<u64>,
+    /// The nodes from the start node to `node_id`, both included, or empty if paths are
+    /// disabled. The label of the start node has the path `[start]`.
     pub path: Vec<T>,
     pub node_id: T,
+    /// State of the automaton of a regular-language constrained query, 0 otherwise. Labels only
+    /// dominate labels of the same state.
+    pub state: usize,
+    /// Line of the edge the label arrived by, None if it did not traverse an edge yet. Labels
+    /// only dominate labels that arrived on the same line, as transfer penalties depend on it,
+    /// and a label without a line dominates regardless of the line. For equality, None counts
+    /// as line 0.
+    pub line: Option<u32>,
+    /// Index of the edge the label arrived by if turn costs start at it, None otherwise, see
+    /// `mlc::turns::TurnCosts`. Labels only dominate labels that arrived by the same edge or
+    /// have no edge, as turn costs depend on it.
+    pub edge: Option<usize>,
+}
+
+impl<T> Label<T> {
+    /// Returns the value of the given criterion. Panics if the criterion does not exist.
+    pub fn criterion(&self, criterion: Criterion) -> u64 {
+        match criterion {
+            Criterion::Value(index) => self.values[index],
+            Criterion::HiddenValue(index) => self.hidden_values[index],
+        }
+    }
+
+    fn line_or_default(&self) -> u32 {
+        self.line.unwrap_or(0)
+    }
 }
 
 impl Label<NodeId> {
@@ -39,13 +153,34 @@ impl Label<NodeId> {
         edge: &EdgeReference<WeightsTuple>,
         disable_path: bool,
     ) -> Label<NodeId> {
-        let weight = edge.weight();
-        let values = self
+        self.new_along_weights(
+            edge.weight().as_edge_weights(),
+            edge.target().index(),
+            disable_path,
+        )
+    }
+
+    /// Like `new_along`, but with the weights and target of the edge given explicitly. The target
+    /// is appended to the path, so the path ends at the new label's node.
+    ///
+    /// If the label arrived by an edge of another line, the transfer penalty and the waiting
+    /// cost of the edge are added to its weights. Turn costs are added by `add_turn_weights`.
+    pub fn new_along_weights(
+        &self,
+        weight: EdgeWeights,
+        target_node_id: NodeId,
+        disable_path: bool,
+    ) -> Label<NodeId> {
+        let mut values: Vec<u64> = self
             .values
             .iter()
             .zip(weight.weights.iter())
             .map(|(a, b)| a + b)
             .collect();
+        if self.line.is_some_and(|line| line != weight.line) {
+            add_weights(&mut values, weight.transfer_weights);
+            add_weights(&mut values, weight.wait_weights);
+        }
         let hidden_values = self
             .hidden_values
             .iter()
@@ -58,26 +193,49 @@ impl Label<NodeId> {
         } else {
             self.path.clone()
         };
-        let target_node_id = edge.target().index();
         if !disable_path {
-            path.push(self.node_id);
+            path.push(target_node_id);
         }
         Label {
             values,
             path,
             node_id: target_node_id,
             hidden_values,
+            state: self.state,
+            line: Some(weight.line),
+            edge: None,
         }
     }
 
+    /// Adds the cost of the turn from the edge the label arrived by onto its next edge, e.g.
+    /// to a label returned by `new_along`.
+    pub fn add_turn_weights(&mut self, turn_weights: &[Weight]) {
+        add_weights(&mut self.values, turn_weights);
+    }
+
+    /// Returns true if the path of the label goes from `u` directly to `v`.
+    pub fn path_uses(&self, u: NodeId, v: NodeId) -> bool {
+        self.path.windows(2).any(|w| w[0] == u && w[1] == v)
+    }
+
     // returns true if the label weakly dominates the other label
     // this is the case if it either strictly dominates the other label
     // or if it is equal to the other label
     fn weakly_dominates(&self, other: &Label<NodeId>) -> bool {
-        self.values
-            .iter()
-            .zip(other.values.iter())
-            .all(|(a, b)| a <= b)
+        self.state == other.state
+            && (self.line.is_none() || self.line == other.line)
+            && (self.edge.is_none() || self.edge == other.edge)
+            && self
+                .values
+                .iter()
+                .zip(other.values.iter())
+                .all(|(a, b)| a <= b)
+    }
+}
+
+fn add_weights(values: &mut [u64], weights: &[Weight]) {
+    for (value, weight) in values.iter_mut().zip(weights.iter()) {
+        *value += weight;
     }
 }
 
@@ -88,26 +246,27 @@ impl<T> Ord for Label<T> {
         match self.values.cmp(&other.values) {
             Ordering::Less => Ordering::Greater,
             Ordering::Greater => Ordering::Less,
-            Ordering::Equal => Ordering::Equal,
+            Ordering::Equal => (other.state, other.line_or_default(), other.edge).cmp(&(
+                self.state,
+                self.line_or_default(),
+                self.edge,
+            )),
         }
     }
 }
 
 impl<T> PartialOrd for Label<T> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        // lexicographical order, but the smaller the better
-        // we need a "min-heap" as queue
-        match self.values.cmp(&other.values) {
-            Ordering::Less => Some(Ordering::Greater),
-            Ordering::Greater => Some(Ordering::Less),
-            Ordering::Equal => Some(Ordering::Equal),
-        }
+        Some(self.cmp(other))
     }
 }
 
 impl<T> PartialEq for Label<T> {
     fn eq(&self, other: &Self) -> bool {
         self.values == other.values
+            && self.state == other.state
+            && self.line_or_default() == other.line_or_default()
+            && self.edge == other.edge
     }
 }
 
@@ -116,6 +275,9 @@ impl<T> Eq for Label<T> {}
 impl<T> Hash for Label<T> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.values.hash(state);
+        self.state.hash(state);
+        self.line_or_default().hash(state);
+        self.edge.hash(state);
     }
 }
 
@@ -138,12 +300,18 @@ impl Bag<NodeId> {
     }
 
     pub fn add_if_necessary(&mut self, label: Label<NodeId>) -> bool {
+        self.add_if_necessary_counting_removed(label).is_some()
+    }
+
+    /// Like `add_if_necessary`, but returns the number of labels that were removed from the
+    /// bag because the new label dominated them, or `None` if the label was not added.
+    pub fn add_if_necessary_counting_removed(&mut self, label: Label<NodeId>) -> Option<usize> {
         if self.content_dominates(&label) {
-            return false;
+            return None;
         }
-        self.remove_dominated_by(&label);
+        let n_removed = self.remove_dominated_by(&label);
         self.labels.insert(label);
-        true
+        Some(n_removed)
     }
 
     pub fn content_dominates(&self, label: &Label<NodeId>) -> bool {
@@ -155,7 +323,61 @@ impl Bag<NodeId> {
         false
     }
 
-    fn remove_dominated_by(&mut self, label: &Label<NodeId>) {
+    /// Returns the labels of the bag that are not dominated by the values of another label of
+    /// the same state, regardless of their line and edge. Of labels with equal values and state,
+    /// the one with the smallest line and edge is kept.
+    ///
+    /// Labels that arrived on different lines or by different edges do not dominate each other
+    /// during the search, as their costs may still differ, so a bag may hold dominated labels.
+    pub fn front(&self) -> Bag<NodeId> {
+        self.front_by(|a, b| a.state == b.state)
+    }
+
+    /// Like `front`, but labels also dominate labels of other states. Of labels with equal
+    /// values, the one with the smallest state is kept.
+    pub fn front_across_states(&self) -> Bag<NodeId> {
+        self.front_by(|_, _| true)
+    }
+
+    // returns true if the labels arrived on more than one line or by more than one edge, i.e.
+    // if the bag may hold dominated labels
+    pub(crate) fn has_several_contexts(&self) -> bool {
+        let mut contexts = self
+            .labels
+            .iter()
+            .map(|label| (label.line_or_default(), label.edge));
+        let first = contexts.next();
+        contexts.any(|context| Some(context) != first)
+    }
+
+    fn front_by(&self, comparable: impl Fn(&Label<NodeId>, &Label<NodeId>) -> bool) -> Bag<NodeId> {
+        let mut labels = self.labels.iter().collect::<Vec<_>>();
+        // a label can only be dominated by labels before it
+        labels.sort_by(|a, b| {
+            (&a.values, a.state, a.line, a.edge).cmp(&(&b.values, b.state, b.line, b.edge))
+        });
+        let mut front: Vec<&Label<NodeId>> = vec![];
+        for label in labels {
+            let is_dominated = front.iter().any(|other| {
+                comparable(other, label)
+                    && other
+                        .values
+                        .iter()
+                        .zip(label.values.iter())
+                        .all(|(a, b)| a <= b)
+            });
+            if !is_dominated {
+                front.push(label);
+            }
+        }
+        Bag {
+            labels: front.into_iter().cloned().collect(),
+        }
+    }
+
+    fn remove_dominated_by(&mut self, label: &Label<NodeId>) -> usize {
+        let n_labels = self.labels.len();
         self.labels.retain(|l| !label.weakly_dominates(l));
+        n_labels - self.labels.len()
     }
 }
@@ -9,36 +9,54 @@ mod tests {
             hidden_values: vec![],
             path: vec![0, 1, 2],
             node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
         };
         let label2 = Label {
             values: vec![1, 2, 3],
             hidden_values: vec![],
             path: vec![0, 1, 2],
             node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
         };
         let label3 = Label {
             values: vec![2, 3, 4],
             hidden_values: vec![],
             path: vec![0, 1, 2],
             node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
         };
         let label4 = Label {
             values: vec![1, 2, 4],
             hidden_values: vec![],
             path: vec![0, 1, 2],
             node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
         };
         let label_bug_1 = Label {
             values: vec![1852375, 0],
             hidden_values: vec![],
             path: vec![0],
             node_id: 1,
+            state: 0,
+            line: None,
+            edge: None,
         };
         let label_bug_2 = Label {
             values: vec![2003938, 0],
             hidden_values: vec![],
             path: vec![0],
             node_id: 1,
+            state: 0,
+            line: None,
+            edge: None,
         };
 
         assert!(label1.weakly_dominates(&label2));
@@ -58,12 +76,18 @@ mod tests {
             hidden_values: vec![],
             path: vec![0, 1, 2],
             node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
         };
         let label2 = Label {
             values: vec![2, 3, 4],
             hidden_values: vec![],
             path: vec![0, 1, 2],
             node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
         };
 
         assert!(bag.add_if_necessary(label1.clone()));
@@ -77,6 +101,9 @@ mod tests {
             hidden_values: vec![],
             path: vec![0, 1, 2],
             node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
         };
         assert!(bag.add_if_necessary(label3.clone()));
         assert_eq!(bag.labels.len(), 2);
@@ -94,25 +121,104 @@ mod tests {
             hidden_values: vec![],
             path: vec![0, 1, 2],
             node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
         };
         let label2 = Label {
             values: vec![2, 3, 4],
             hidden_values: vec![],
             path: vec![0, 1, 2],
             node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
         };
         let label3 = Label {
             values: vec![0, 0, 0],
             hidden_values: vec![],
             path: vec![0, 1, 2],
             node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
         };
 
         bag.labels.insert(label1);
         bag.labels.insert(label2);
         assert_eq!(bag.labels.len(), 2);
 
-        bag.remove_dominated_by(&label3);
+        assert_eq!(bag.remove_dominated_by(&label3), 2);
         assert_eq!(bag.labels.len(), 0);
     }
+
+    #[test]
+    fn test_bag_add_if_necessary_counting_removed() {
+        let mut bag = Bag::new_empty();
+        let label1 = Label {
+            values: vec![1, 3],
+            hidden_values: vec![],
+            path: vec![0, 1, 2],
+            node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
+        };
+        let label2 = Label {
+            values: vec![3, 1],
+            hidden_values: vec![],
+            path: vec![0, 1, 2],
+            node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
+        };
+        let label3 = Label {
+            values: vec![1, 1],
+            hidden_values: vec![],
+            path: vec![0, 1, 2],
+            node_id: 2,
+            state: 0,
+            line: None,
+            edge: None,
+        };
+
+        assert_eq!(
+            bag.add_if_necessary_counting_removed(label1.clone()),
+            Some(0)
+        );
+        assert_eq!(bag.add_if_necessary_counting_removed(label2), Some(0));
+        assert_eq!(bag.add_if_necessary_counting_removed(label3), Some(2));
+        assert_eq!(bag.add_if_necessary_counting_removed(label1), None);
+        assert_eq!(bag.labels.len(), 1);
+    }
+
+    #[test]
+    fn test_new_along_weights_appends_target_to_path() {
+        let start = Label {
+            values: vec![0, 0],
+            hidden_values: vec![],
+            path: vec![0],
+            node_id: 0,
+            state: 0,
+            line: None,
+            edge: None,
+        };
+        let weights = WeightsTuple {
+            weights: vec![1, 2],
+            ..Default::default()
+        };
+
+        let label = start.new_along_weights(weights.as_edge_weights(), 1, false);
+        assert_eq!(label.values, vec![1, 2]);
+        assert_eq!(label.path, vec![0, 1]);
+        assert!(label.path_uses(0, 1));
+
+        let label = label.new_along_weights(weights.as_edge_weights(), 2, false);
+        assert_eq!(label.path, vec![0, 1, 2]);
+        assert_eq!(label.path.last(), Some(&label.node_id));
+
+        let label = start.new_along_weights(weights.as_edge_weights(), 1, true);
+        assert!(label.path.is_empty());
+    }
 }
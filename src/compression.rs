@@ -0,0 +1,97 @@
+mod test;
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the compression of a file by its extension, `.gz` for gzip and `.zst` or `.zstd`
+    /// for zstd.
+    pub fn from_extension(path: &str) -> Compression {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst" | "zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Opens a file for reading, decompressing it while reading if it starts with the magic bytes of
+/// gzip or zstd. The extension does not matter, so compressed files can keep their old names.
+pub fn open(path: &str) -> io::Result<Box<dyn BufRead>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let magic = file.fill_buf()?;
+    if magic.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(file))))
+    } else if magic.starts_with(&ZSTD_MAGIC) {
+        Ok(Box::new(BufReader::new(zstd::Decoder::with_buffer(file)?)))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+/// Creates a file for writing, compressed by its extension, see `Compression::from_extension`.
+/// Call `finish` when done, which reports errors that dropping the writer would ignore.
+pub fn create(path: &str) -> io::Result<Writer> {
+    let file = BufWriter::new(File::create(path)?);
+    let encoder = match Compression::from_extension(path) {
+        Compression::None => Encoder::Plain(file),
+        Compression::Gzip => Encoder::Gzip(GzEncoder::new(file, flate2::Compression::default())),
+        Compression::Zstd => Encoder::Zstd(zstd::Encoder::new(file, 0)?),
+    };
+    Ok(Writer { encoder })
+}
+
+/// A file writer that compresses its output, see `create`.
+pub struct Writer {
+    encoder: Encoder,
+}
+
+enum Encoder {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl Writer {
+    /// Writes the end of the compressed stream and flushes the file.
+    pub fn finish(self) -> io::Result<()> {
+        let mut file = match self.encoder {
+            Encoder::Plain(file) => file,
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            Encoder::Zstd(encoder) => encoder.finish()?,
+        };
+        file.flush()
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.encoder {
+            Encoder::Plain(file) => file.write(buf),
+            Encoder::Gzip(encoder) => encoder.write(buf),
+            Encoder::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            Encoder::Plain(file) => file.flush(),
+            Encoder::Gzip(encoder) => encoder.flush(),
+            Encoder::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use crate::compression::Compression;
+    use crate::mlc;
+    use crate::read;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("mlc_test_{}_{}", std::process::id(), name));
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_compression_from_extension() {
+        assert_eq!(
+            Compression::from_extension("labels.csv.gz"),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::from_extension("labels.csv.zst"),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::from_extension("labels.zstd"),
+            Compression::Zstd
+        );
+        assert_eq!(Compression::from_extension("labels.csv"), Compression::None);
+        assert_eq!(Compression::from_extension("gz"), Compression::None);
+    }
+
+    #[test]
+    fn test_compressed_bags() {
+        let bags = mlc::read_bags("testdata/results.csv").unwrap();
+        for (name, magic) in [
+            ("labels.csv.gz", vec![0x1f, 0x8b]),
+            ("labels.csv.zst", vec![0x28, 0xb5, 0x2f, 0xfd]),
+            ("labels.csv", b"node_id|".to_vec()),
+        ] {
+            let path = temp_path(name);
+            mlc::write_bags(&bags, &path).unwrap();
+            assert!(std::fs::read(&path).unwrap().starts_with(&magic));
+            assert_eq!(mlc::read_bags(&path).unwrap(), bags);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_compressed_graph() {
+        let g = read::read_graph_with_int_ids("testdata/edges_transfers.csv").unwrap();
+        let path = temp_path("edges.csv.zst");
+        read::write_graph(&g, None, &path).unwrap();
+        // compression is detected by the magic bytes, whatever the extension
+        let renamed = temp_path("edges_zst.csv");
+        std::fs::rename(&path, &renamed).unwrap();
+        let read_graph = read::read_graph_with_int_ids(&renamed).unwrap();
+        assert_eq!(
+            read_graph.edge_weights().collect::<Vec<_>>(),
+            g.edge_weights().collect::<Vec<_>>()
+        );
+        let csr = read::read_csr_graph_with_int_ids(&renamed).unwrap();
+        assert_eq!(crate::graph::SearchGraph::edge_count(&csr), g.edge_count());
+        std::fs::remove_file(&renamed).unwrap();
+
+        let path = temp_path("edges.csv.gz");
+        read::write_graph(&g, None, &path).unwrap();
+        let (read_graph, node_map) = read::read_graph_and_reset_ids(&path).unwrap();
+        assert_eq!(read_graph.edge_count(), g.edge_count());
+        assert_eq!(node_map.len(), g.node_count());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,131 @@
+mod test;
+
+use std::collections::BTreeSet;
+use std::error::Error;
+
+use serde::Serialize;
+
+use crate::analysis::pareto_front;
+use crate::bag::Weight;
+use crate::mlc::{read_bags, Bags};
+
+/// Differences between the labels of two runs A and B, see `diff_bags`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LabelsDiff {
+    /// The nodes whose labels differ, ordered by node id.
+    pub nodes: Vec<NodeDiff>,
+    pub summary: Summary,
+}
+
+/// Differences between the labels of a node, by their values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeDiff {
+    pub node_id: usize,
+    pub only_in_a: Vec<Vec<Weight>>,
+    pub only_in_b: Vec<Vec<Weight>>,
+    /// Labels only in A that are dominated by a label of B, i.e. that got better in B.
+    pub a_dominated_by_b: Vec<Dominance>,
+    /// Labels only in B that are dominated by a label of A, i.e. that got worse in B.
+    pub b_dominated_by_a: Vec<Dominance>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Dominance {
+    pub label: Vec<Weight>,
+    /// The smallest dominating label of the other run.
+    pub by: Vec<Weight>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub nodes_in_a: usize,
+    pub nodes_in_b: usize,
+    pub nodes_only_in_a: usize,
+    pub nodes_only_in_b: usize,
+    pub nodes_with_differences: usize,
+    pub labels_only_in_a: usize,
+    pub labels_only_in_b: usize,
+    pub labels_in_a_dominated_by_b: usize,
+    pub labels_in_b_dominated_by_a: usize,
+}
+
+impl LabelsDiff {
+    /// Returns whether both runs have the same labels.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+/// Reads two labels files written by `write_bags` and compares them, see `diff_bags`.
+pub fn diff_files(path_a: &str, path_b: &str) -> Result<LabelsDiff, Box<dyn Error>> {
+    let a = read_bags(path_a).map_err(|err| format!("Could not read {}: {}", path_a, err))?;
+    let b = read_bags(path_b).map_err(|err| format!("Could not read {}: {}", path_b, err))?;
+    Ok(diff_bags(&a, &b))
+}
+
+/// Compares the labels of two runs node by node.
+///
+/// Labels are compared by their values only, as paths of labels with equal values may differ
+/// between correct runs, and hidden values are ignored. For labels files of the older format,
+/// the values include the hidden values, see `mlc::read_labels`. A node that is only reached by one
+/// run has all its labels only in that run.
+pub fn diff_bags(a: &Bags<usize>, b: &Bags<usize>) -> LabelsDiff {
+    let node_ids = a.keys().chain(b.keys()).copied().collect::<BTreeSet<_>>();
+    let mut summary = Summary {
+        nodes_in_a: a.len(),
+        nodes_in_b: b.len(),
+        nodes_only_in_a: a.keys().filter(|node_id| !b.contains_key(node_id)).count(),
+        nodes_only_in_b: b.keys().filter(|node_id| !a.contains_key(node_id)).count(),
+        ..Default::default()
+    };
+
+    let mut nodes = vec![];
+    for node_id in node_ids {
+        let front_a = a.get(&node_id).map(pareto_front).unwrap_or_default();
+        let front_b = b.get(&node_id).map(pareto_front).unwrap_or_default();
+        let only_in_a = missing_from(&front_a, &front_b);
+        let only_in_b = missing_from(&front_b, &front_a);
+        if only_in_a.is_empty() && only_in_b.is_empty() {
+            continue;
+        }
+        let a_dominated_by_b = dominated_by(&only_in_a, &front_b);
+        let b_dominated_by_a = dominated_by(&only_in_b, &front_a);
+
+        summary.nodes_with_differences += 1;
+        summary.labels_only_in_a += only_in_a.len();
+        summary.labels_only_in_b += only_in_b.len();
+        summary.labels_in_a_dominated_by_b += a_dominated_by_b.len();
+        summary.labels_in_b_dominated_by_a += b_dominated_by_a.len();
+        nodes.push(NodeDiff {
+            node_id,
+            only_in_a,
+            only_in_b,
+            a_dominated_by_b,
+            b_dominated_by_a,
+        });
+    }
+    LabelsDiff { nodes, summary }
+}
+
+fn missing_from(front: &[Vec<Weight>], other: &[Vec<Weight>]) -> Vec<Vec<Weight>> {
+    front
+        .iter()
+        .filter(|values| other.binary_search(values).is_err())
+        .cloned()
+        .collect()
+}
+
+fn dominated_by(labels: &[Vec<Weight>], other: &[Vec<Weight>]) -> Vec<Dominance> {
+    labels
+        .iter()
+        .filter_map(|label| {
+            let by = other
+                .iter()
+                .find(|values| values.iter().zip(label).all(|(v, l)| v <= l))?;
+            Some(Dominance {
+                label: label.clone(),
+                by: by.clone(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::diff::*;
+    use crate::mlc;
+
+    #[test]
+    fn test_diff_equal_files() {
+        let diff = diff_files("testdata/results.csv", "testdata/results.csv").unwrap();
+        assert!(diff.is_empty());
+        assert_eq!(diff.summary.nodes_in_a, 5);
+        assert_eq!(diff.summary.nodes_with_differences, 0);
+        assert!(diff_files("testdata/results.csv", "testdata/missing.csv").is_err());
+    }
+
+    #[test]
+    fn test_diff_bags() {
+        let a = mlc::read_bags("testdata/results.csv").unwrap();
+        let mut b = a.clone();
+        // (0;3) at node 2 got worse, (1;2) got lost and node 4 is not reached
+        let bag = b.get_mut(&2).unwrap();
+        let mut worse = bag
+            .labels
+            .iter()
+            .find(|l| l.values == [0, 3])
+            .unwrap()
+            .clone();
+        bag.labels
+            .retain(|l| l.values != [0, 3] && l.values != [1, 2]);
+        worse.values = vec![1, 3];
+        bag.labels.insert(worse);
+        b.remove(&4);
+
+        let diff = diff_bags(&a, &b);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.nodes.len(), 2);
+        let node = &diff.nodes[0];
+        assert_eq!(node.node_id, 2);
+        assert_eq!(node.only_in_a, vec![vec![0, 3], vec![1, 2]]);
+        assert_eq!(node.only_in_b, vec![vec![1, 3]]);
+        assert!(node.a_dominated_by_b.is_empty());
+        assert_eq!(
+            node.b_dominated_by_a,
+            vec![Dominance {
+                label: vec![1, 3],
+                by: vec![0, 3],
+            }]
+        );
+        assert_eq!(diff.nodes[1].node_id, 4);
+        assert_eq!(diff.nodes[1].only_in_a.len(), 16);
+        assert_eq!(
+            diff.summary,
+            Summary {
+                nodes_in_a: 5,
+                nodes_in_b: 4,
+                nodes_only_in_a: 1,
+                nodes_only_in_b: 0,
+                nodes_with_differences: 2,
+                labels_only_in_a: 18,
+                labels_only_in_b: 1,
+                labels_in_a_dominated_by_b: 0,
+                labels_in_b_dominated_by_a: 1,
+            }
+        );
+
+        // the other way around, the worse label is dominated by A
+        let diff = diff_bags(&b, &a);
+        assert_eq!(diff.nodes[0].a_dominated_by_b.len(), 1);
+        assert_eq!(diff.summary.nodes_only_in_b, 1);
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["summary"]["labels_only_in_b"], 18);
+        assert_eq!(json["nodes"][0]["only_in_a"][0], serde_json::json!([1, 3]));
+    }
+}
@@ -0,0 +1,166 @@
+mod test;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::hash::Hash;
+
+use bimap::BiMap;
+use serde_json::{json, Value};
+
+use crate::analysis::{ideal_point, pareto_front};
+use crate::bag::{Bag, Label};
+use crate::compression;
+use crate::mlc::{translate_bag, translate_bags, Bags};
+
+/// Longitude and latitude of each node, by external node id.
+pub type Coordinates = HashMap<String, (f64, f64)>;
+
+// Reads node coordinates from a csv file with a header and the columns node id, longitude and
+// latitude, in this order. Further columns are ignored. Compressed files are supported, see
+// compression::open.
+pub fn read_coordinates(path: &str) -> Result<Coordinates, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .quote(b'"')
+        .from_reader(compression::open(path)?);
+    let mut coordinates = HashMap::new();
+    for (i, result) in rdr.records().enumerate() {
+        let record = result?;
+        let field = |column: usize| {
+            record
+                .get(column)
+                .ok_or_else(|| format!("Row {} of {} has less than 3 columns", i + 1, path))
+        };
+        let lon = field(1)?.trim().parse::<f64>()?;
+        let lat = field(2)?.trim().parse::<f64>()?;
+        coordinates.insert(field(0)?.to_string(), (lon, lat));
+    }
+    Ok(coordinates)
+}
+
+/// Returns the labels of the bag as a GeoJSON FeatureCollection of LineStrings along their paths.
+///
+/// Nodes are looked up in `coordinates` by their `Display` form, so the bag must be translated
+/// to external ids (see `mlc::translate_bags`) unless the graph was read with integer ids. The
+/// features have the properties `node_id`, `rank`, `values` and `hidden_values`, where `rank` is
+/// the position of the label when the labels are sorted by their values. Labels whose path is
+/// only the start node are Points.
+pub fn paths_to_geojson<T: Eq + Hash + Display>(
+    bag: &Bag<T>,
+    coordinates: &Coordinates,
+) -> Result<Value, Box<dyn Error>> {
+    let mut labels = bag.labels.iter().collect::<Vec<_>>();
+    labels.sort_by(|a, b| (&a.values, &a.hidden_values).cmp(&(&b.values, &b.hidden_values)));
+    let features = labels
+        .into_iter()
+        .enumerate()
+        .map(|(rank, label)| {
+            let geometry = path_geometry(label, coordinates)?;
+            Ok(feature(
+                geometry,
+                json!({
+                    "node_id": label.node_id.to_string(),
+                    "rank": rank,
+                    "values": label.values,
+                    "hidden_values": label.hidden_values,
+                }),
+            ))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    Ok(feature_collection(features))
+}
+
+/// Returns the reached nodes as a GeoJSON FeatureCollection of Points, with the properties
+/// `node_id`, `labels`, the size of the Pareto front of the node, and `best_values`, the best
+/// value of each criterion over its labels. Nodes are looked up as in `paths_to_geojson`.
+pub fn reached_nodes_to_geojson<T: Eq + Hash + Display>(
+    bags: &Bags<T>,
+    coordinates: &Coordinates,
+) -> Result<Value, Box<dyn Error>> {
+    let features = bags
+        .iter()
+        .filter(|(_, bag)| !bag.labels.is_empty())
+        .map(|(node_id, bag)| {
+            let front = pareto_front(bag);
+            Ok(feature(
+                point(node_id, coordinates)?,
+                json!({
+                    "node_id": node_id.to_string(),
+                    "labels": front.len(),
+                    "best_values": ideal_point(&front),
+                }),
+            ))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    Ok(feature_collection(features))
+}
+
+/// Like `paths_to_geojson`, but translates the nodes of the bag to the external node ids of the
+/// node map first, see `mlc::translate_bag`.
+pub fn translated_paths_to_geojson(
+    bag: &Bag<usize>,
+    node_map: &BiMap<String, usize>,
+    coordinates: &Coordinates,
+) -> Result<Value, Box<dyn Error>> {
+    paths_to_geojson(&translate_bag(bag, node_map)?, coordinates)
+}
+
+/// Like `reached_nodes_to_geojson`, but translates the nodes to the external node ids of the
+/// node map first, see `mlc::translate_bags`.
+pub fn translated_reached_nodes_to_geojson(
+    bags: &Bags<usize>,
+    node_map: &BiMap<String, usize>,
+    coordinates: &Coordinates,
+) -> Result<Value, Box<dyn Error>> {
+    reached_nodes_to_geojson(&translate_bags(bags, node_map)?, coordinates)
+}
+
+/// Writes the GeoJSON to a file, compressed if the path ends with `.gz` or `.zst`, see
+/// `compression::create`.
+pub fn write_geojson(geojson: &Value, path: &str) -> Result<(), Box<dyn Error>> {
+    let mut writer = compression::create(path)?;
+    serde_json::to_writer(&mut writer, geojson)?;
+    writer.finish()?;
+    Ok(())
+}
+
+fn path_geometry<T: Display>(
+    label: &Label<T>,
+    coordinates: &Coordinates,
+) -> Result<Value, Box<dyn Error>> {
+    match label.path.as_slice() {
+        [] => Err(format!(
+            "Label of node {} has no path, paths must not be disabled",
+            label.node_id
+        )
+        .into()),
+        [node] => point(node, coordinates),
+        path => {
+            let positions = path
+                .iter()
+                .map(|node| position(node, coordinates))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(json!({ "type": "LineString", "coordinates": positions }))
+        }
+    }
+}
+
+fn point<T: Display>(node: &T, coordinates: &Coordinates) -> Result<Value, Box<dyn Error>> {
+    Ok(json!({ "type": "Point", "coordinates": position(node, coordinates)? }))
+}
+
+fn position<T: Display>(node: &T, coordinates: &Coordinates) -> Result<[f64; 2], Box<dyn Error>> {
+    let node = node.to_string();
+    let (lon, lat) = coordinates
+        .get(&node)
+        .ok_or_else(|| format!("Node {} has no coordinates", node))?;
+    Ok([*lon, *lat])
+}
+
+fn feature(geometry: Value, properties: Value) -> Value {
+    json!({ "type": "Feature", "geometry": geometry, "properties": properties })
+}
+
+fn feature_collection(features: Vec<Value>) -> Value {
+    json!({ "type": "FeatureCollection", "features": features })
+}
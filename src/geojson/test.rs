@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use bimap::BiMap;
+    use serde_json::json;
+
+    use crate::compression;
+    use crate::geojson::*;
+    use crate::mlc::MLC;
+    use crate::read;
+
+    #[test]
+    fn test_paths_to_geojson() {
+        let coordinates = read_coordinates("testdata/coordinates.csv").unwrap();
+        assert_eq!(coordinates["4"], (13.4, 52.2));
+        let g = read::read_graph_with_int_ids("testdata/edges.csv").unwrap();
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+
+        let geojson = paths_to_geojson(&bags[&2], &coordinates).unwrap();
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 4);
+        for (rank, feature) in features.iter().enumerate() {
+            assert_eq!(feature["properties"]["rank"], rank);
+            assert_eq!(feature["properties"]["node_id"], "2");
+            assert_eq!(feature["properties"]["values"], json!([rank, 3 - rank]));
+            assert_eq!(
+                feature["geometry"],
+                json!({
+                    "type": "LineString",
+                    "coordinates": [[13.0, 52.0], [13.1, 52.0], [13.2, 52.1]],
+                })
+            );
+        }
+        // the start label is a point
+        let geojson = paths_to_geojson(&bags[&0], &coordinates).unwrap();
+        assert_eq!(geojson["features"][0]["geometry"]["type"], "Point");
+
+        let mut missing = coordinates.clone();
+        missing.remove("1");
+        assert!(paths_to_geojson(&bags[&2], &missing).is_err());
+    }
+
+    #[test]
+    fn test_reached_nodes_to_geojson() {
+        let coordinates = read_coordinates("testdata/coordinates.csv").unwrap();
+        let g = read::read_graph_with_int_ids("testdata/edges.csv").unwrap();
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(1);
+        let bags = mlc.run().unwrap();
+
+        let geojson = reached_nodes_to_geojson(bags, &coordinates).unwrap();
+        let mut features = geojson["features"].as_array().unwrap().clone();
+        features.sort_by_key(|feature| feature["properties"]["node_id"].to_string());
+        assert_eq!(features.len(), 4);
+        assert_eq!(
+            features[3],
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [13.4, 52.2] },
+                "properties": { "node_id": "4", "labels": 8, "best_values": [0, 0] },
+            })
+        );
+
+        for extension in ["json", "json.gz"] {
+            let path = std::env::temp_dir().join(format!(
+                "mlc_test_geojson_{}.{}",
+                std::process::id(),
+                extension
+            ));
+            let path = path.to_str().unwrap();
+            write_geojson(&geojson, path).unwrap();
+            let read: serde_json::Value =
+                serde_json::from_reader(compression::open(path).unwrap()).unwrap();
+            assert_eq!(read, geojson);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_translated_geojson() {
+        let coordinates = read_coordinates("testdata/coordinates.csv").unwrap();
+        let g = read::read_graph_with_int_ids("testdata/edges.csv").unwrap();
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        // reverses the node ids
+        let node_map = (0..5)
+            .map(|id| ((4 - id).to_string(), id))
+            .collect::<BiMap<_, _>>();
+
+        let geojson = translated_paths_to_geojson(&bags[&1], &node_map, &coordinates).unwrap();
+        let feature = &geojson["features"][0];
+        assert_eq!(feature["properties"]["node_id"], "3");
+        assert_eq!(
+            feature["geometry"]["coordinates"],
+            json!([[13.4, 52.2], [13.3, 52.1]])
+        );
+
+        let geojson = translated_reached_nodes_to_geojson(bags, &node_map, &coordinates).unwrap();
+        assert_eq!(geojson["features"].as_array().unwrap().len(), 5);
+
+        let mut missing = node_map.clone();
+        missing.remove_by_right(&1);
+        assert!(translated_paths_to_geojson(&bags[&1], &missing, &coordinates).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+mod test;
+
+pub mod chains;
+pub mod csr;
+pub mod hierarchy;
+pub mod subgraph;
+
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::{Directed, Direction, Graph};
+
+use crate::bag::{EdgeWeights, NodeId, WeightsTuple};
+
+pub use self::csr::CsrGraph;
+
+/// An outgoing edge of a node, as handed out by a `SearchGraph`.
+#[derive(Debug, Clone, Copy)]
+pub struct OutEdge<'a> {
+    pub id: EdgeIndex,
+    pub target: NodeId,
+    pub weights: EdgeWeights<'a>,
+}
+
+/// The read-only graph operations `MLC` needs, so that it can run on different graph backends,
+/// e.g. a petgraph `Graph` or a `CsrGraph`. Nodes are identified by their integer id, edges by
+/// an `EdgeIndex` whose meaning is up to the backend.
+pub trait SearchGraph<T> {
+    fn node_count(&self) -> usize;
+
+    fn edge_count(&self) -> usize;
+
+    /// Returns the categories of the node, or `None` if the node does not exist.
+    fn categories(&self, node: NodeId) -> Option<&[T]>;
+
+    /// Returns the outgoing edges of the node, none if the node does not exist.
+    fn out_edges(&self, node: NodeId) -> impl Iterator<Item = OutEdge<'_>>;
+
+    /// Returns the nodes with an edge to the node. A node may be returned more than once.
+    fn predecessors(&self, node: NodeId) -> impl Iterator<Item = NodeId>;
+
+    fn edge_endpoints(&self, edge: EdgeIndex) -> Option<(NodeId, NodeId)>;
+
+    fn edge_weights(&self, edge: EdgeIndex) -> Option<EdgeWeights<'_>>;
+}
+
+impl<T> SearchGraph<T> for Graph<Vec<T>, WeightsTuple, Directed> {
+    fn node_count(&self) -> usize {
+        Graph::node_count(self)
+    }
+
+    fn edge_count(&self) -> usize {
+        Graph::edge_count(self)
+    }
+
+    fn categories(&self, node: NodeId) -> Option<&[T]> {
+        self.node_weight(NodeIndex::new(node))
+            .map(|categories| categories.as_slice())
+    }
+
+    fn out_edges(&self, node: NodeId) -> impl Iterator<Item = OutEdge<'_>> {
+        self.edges(NodeIndex::new(node)).map(|edge| OutEdge {
+            id: edge.id(),
+            target: edge.target().index(),
+            weights: edge.weight().as_edge_weights(),
+        })
+    }
+
+    fn predecessors(&self, node: NodeId) -> impl Iterator<Item = NodeId> {
+        self.neighbors_directed(NodeIndex::new(node), Direction::Incoming)
+            .map(|predecessor| predecessor.index())
+    }
+
+    fn edge_endpoints(&self, edge: EdgeIndex) -> Option<(NodeId, NodeId)> {
+        Graph::edge_endpoints(self, edge).map(|(u, v)| (u.index(), v.index()))
+    }
+
+    fn edge_weights(&self, edge: EdgeIndex) -> Option<EdgeWeights<'_>> {
+        self.edge_weight(edge)
+            .map(|weight| weight.as_edge_weights())
+    }
+}
@@ -0,0 +1,208 @@
+mod test;
+
+use std::collections::{HashMap, HashSet};
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use petgraph::{Directed, Graph};
+
+use crate::bag::{Bag, Label, NodeId, WeightsTuple};
+use crate::mlc::Bags;
+use crate::read::MLCGraph;
+
+/// A graph whose chains of degree-two nodes were contracted into single edges, see
+/// `contract_chains`. Node ids of the contracted graph differ from the original ones; use
+/// `contracted_node` to translate a start node and `unpack_bags` to translate results back.
+pub struct ContractedGraph<T> {
+    pub graph: MLCGraph<T>,
+    // original node id of each node of the contracted graph
+    original_nodes: Vec<NodeId>,
+    contracted_nodes: HashMap<NodeId, NodeId>,
+    // original nodes that were contracted into the edge between two contracted nodes, in order
+    inner_nodes: HashMap<(NodeId, NodeId), Vec<NodeId>>,
+}
+
+struct ChainEdge {
+    u: NodeId,
+    v: NodeId,
+    weight: WeightsTuple,
+    inner_nodes: Vec<NodeId>,
+}
+
+/// Contracts chains of degree-two nodes into single edges.
+///
+/// A node is contracted if it has no categories, is not in `keep` and either has exactly one
+/// incoming edge from `u` and one outgoing edge to `w`, or exactly the edges `u -> v`, `v -> w`,
+/// `w -> v` and `v -> u`, for distinct nodes `u` and `w`. The edges through the node are merged
+/// into `u -> w` (and `w -> u`) by summing `weights` and `hidden_weights`. The transfer penalty
+/// and waiting cost of the second edge are dropped, as staying on the line never costs them.
+///
+/// Edges are only merged if they have the same attributes, mode and line, so that filters and
+/// transfer penalties behave as on the original graph. A node is not contracted if that would
+/// create a second edge between `u` and `w`, so that paths can be unpacked unambiguously.
+/// Automata that count edges of the same mode see fewer edges on the contracted graph.
+pub fn contract_chains<T: Clone>(g: &MLCGraph<T>, keep: &[NodeId]) -> ContractedGraph<T> {
+    let keep = keep.iter().copied().collect::<HashSet<_>>();
+    let node_count = g.node_count();
+    let mut edges = g
+        .edge_references()
+        .map(|edge| {
+            Some(ChainEdge {
+                u: edge.source().index(),
+                v: edge.target().index(),
+                weight: edge.weight().clone(),
+                inner_nodes: vec![],
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut outgoing = vec![vec![]; node_count];
+    let mut incoming = vec![vec![]; node_count];
+    for (id, edge) in edges.iter().enumerate() {
+        let edge = edge.as_ref().unwrap();
+        outgoing[edge.u].push(id);
+        incoming[edge.v].push(id);
+    }
+
+    let mut contracted = vec![false; node_count];
+    for v in 0..node_count {
+        if keep.contains(&v) || !g[NodeIndex::new(v)].is_empty() {
+            continue;
+        }
+        let edge = |id: &usize| edges[*id].as_ref().unwrap();
+        // pairs of an incoming and an outgoing edge to merge
+        let merges = match (incoming[v].as_slice(), outgoing[v].as_slice()) {
+            ([a], [b]) => vec![(*a, *b)],
+            ([a1, a2], [b1, b2]) => {
+                let (u, w) = (edge(a1).u, edge(a2).u);
+                match (edge(b1).v, edge(b2).v) {
+                    (x, y) if x == w && y == u => vec![(*a1, *b1), (*a2, *b2)],
+                    (x, y) if x == u && y == w => vec![(*a1, *b2), (*a2, *b1)],
+                    _ => continue,
+                }
+            }
+            _ => continue,
+        };
+        let mergeable = merges.iter().all(|(a, b)| {
+            let (a, b) = (edge(a), edge(b));
+            a.u != v
+                && b.v != v
+                && a.u != b.v
+                && !outgoing[a.u].iter().any(|id| edge(id).v == b.v)
+                && a.weight.attributes == b.weight.attributes
+                && a.weight.mode == b.weight.mode
+                && a.weight.line == b.weight.line
+        });
+        if !mergeable {
+            continue;
+        }
+
+        for (a, b) in merges {
+            let first = edges[a].take().unwrap();
+            let second = edges[b].take().unwrap();
+            outgoing[first.u].retain(|id| *id != a);
+            incoming[second.v].retain(|id| *id != b);
+
+            let mut weight = first.weight;
+            add(&mut weight.weights, &second.weight.weights);
+            add(&mut weight.hidden_weights, &second.weight.hidden_weights);
+            let mut inner_nodes = first.inner_nodes;
+            inner_nodes.push(v);
+            inner_nodes.extend(second.inner_nodes);
+
+            let id = edges.len();
+            outgoing[first.u].push(id);
+            incoming[second.v].push(id);
+            edges.push(Some(ChainEdge {
+                u: first.u,
+                v: second.v,
+                weight,
+                inner_nodes,
+            }));
+        }
+        incoming[v].clear();
+        outgoing[v].clear();
+        contracted[v] = true;
+    }
+
+    let original_nodes = (0..node_count)
+        .filter(|node| !contracted[*node])
+        .collect::<Vec<_>>();
+    let contracted_nodes = original_nodes
+        .iter()
+        .enumerate()
+        .map(|(contracted, original)| (*original, contracted))
+        .collect::<HashMap<_, _>>();
+    let mut graph = Graph::<Vec<T>, WeightsTuple, Directed>::new();
+    for original in original_nodes.iter() {
+        graph.add_node(g[NodeIndex::new(*original)].clone());
+    }
+    let mut inner_nodes = HashMap::new();
+    for edge in edges.into_iter().flatten() {
+        let u = contracted_nodes[&edge.u];
+        let v = contracted_nodes[&edge.v];
+        graph.add_edge(NodeIndex::new(u), NodeIndex::new(v), edge.weight);
+        if !edge.inner_nodes.is_empty() {
+            inner_nodes.insert((u, v), edge.inner_nodes);
+        }
+    }
+
+    ContractedGraph {
+        graph,
+        original_nodes,
+        contracted_nodes,
+        inner_nodes,
+    }
+}
+
+fn add(values: &mut [u64], weights: &[u64]) {
+    for (value, weight) in values.iter_mut().zip(weights.iter()) {
+        *value += weight;
+    }
+}
+
+impl<T> ContractedGraph<T> {
+    /// Returns the id of the original node in the contracted graph, or `None` if it was
+    /// contracted.
+    pub fn contracted_node(&self, original: NodeId) -> Option<NodeId> {
+        self.contracted_nodes.get(&original).copied()
+    }
+
+    /// Returns the original id of the node of the contracted graph.
+    pub fn original_node(&self, contracted: NodeId) -> NodeId {
+        self.original_nodes[contracted]
+    }
+
+    /// Translates a path of the contracted graph to the original graph, including the nodes
+    /// that were contracted.
+    pub fn unpack_path(&self, path: &[NodeId]) -> Vec<NodeId> {
+        let mut unpacked = vec![];
+        for (i, node) in path.iter().enumerate() {
+            if i > 0 {
+                if let Some(inner_nodes) = self.inner_nodes.get(&(path[i - 1], *node)) {
+                    unpacked.extend(inner_nodes);
+                }
+            }
+            unpacked.push(self.original_node(*node));
+        }
+        unpacked
+    }
+
+    /// Translates bags of a run on the contracted graph to original node ids and unpacks their
+    /// paths. Contracted nodes have no bag.
+    pub fn unpack_bags(&self, bags: &Bags<usize>) -> Bags<usize> {
+        bags.iter()
+            .map(|(node_id, bag)| {
+                let labels = bag
+                    .labels
+                    .iter()
+                    .map(|label| Label {
+                        node_id: self.original_node(label.node_id),
+                        path: self.unpack_path(&label.path),
+                        ..label.clone()
+                    })
+                    .collect();
+                (self.original_node(*node_id), Bag { labels })
+            })
+            .collect()
+    }
+}
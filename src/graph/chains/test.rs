@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::NodeIndex;
+
+    use crate::bag::WeightsTuple;
+    use crate::graph::chains::contract_chains;
+    use crate::mlc::{Bags, MLC};
+    use crate::read::MLCGraph;
+
+    fn weights(weights: Vec<u64>) -> WeightsTuple {
+        WeightsTuple {
+            weights,
+            ..Default::default()
+        }
+    }
+
+    // a two-way chain 0 - 1 - 2 - 3 - 4 next to a direct edge 0 -> 4, and a one-way chain
+    // 4 -> 5 -> 6 -> 0
+    fn chain_graph() -> MLCGraph<&'static str> {
+        let mut g = MLCGraph::new();
+        let nodes = [vec![], vec![], vec![], vec![], vec!["shop"], vec![], vec![]]
+            .into_iter()
+            .map(|categories| g.add_node(categories))
+            .collect::<Vec<_>>();
+        for (u, v, w) in [
+            (0, 1, vec![1, 9]),
+            (1, 0, vec![2, 8]),
+            (1, 2, vec![3, 7]),
+            (2, 1, vec![4, 6]),
+            (2, 3, vec![5, 5]),
+            (3, 2, vec![6, 4]),
+            (3, 4, vec![7, 3]),
+            (4, 3, vec![8, 2]),
+            (0, 4, vec![30, 1]),
+            (4, 5, vec![1, 2]),
+            (5, 6, vec![2, 3]),
+            (6, 0, vec![3, 4]),
+        ] {
+            g.add_edge(nodes[u], nodes[v], weights(w));
+        }
+        g
+    }
+
+    fn run(g: &MLCGraph<&'static str>, start: usize) -> Bags<usize> {
+        let mut mlc = MLC::new(g).unwrap();
+        mlc.set_start_node(start);
+        mlc.run().unwrap().clone()
+    }
+
+    #[test]
+    fn test_contract_chains() {
+        let g = chain_graph();
+        let contracted = contract_chains(&g, &[]);
+        // 3 is kept as contracting it would duplicate the edge 0 -> 4
+        assert_eq!(contracted.graph.node_count(), 3);
+        assert_eq!(contracted.contracted_node(3), Some(1));
+        assert_eq!(contracted.contracted_node(5), None);
+        assert_eq!(contracted.graph[NodeIndex::new(2)], vec!["shop"]);
+        assert_eq!(
+            contracted.unpack_path(&[2, 0, 1]),
+            vec![4, 5, 6, 0, 1, 2, 3]
+        );
+
+        let expected = run(&g, 0);
+        let bags = contracted.unpack_bags(&run(&contracted.graph, 0));
+        assert_eq!(bags.len(), 3);
+        for (node_id, bag) in bags.iter() {
+            assert_eq!(bag, &expected[node_id]);
+            for label in bag.labels.iter() {
+                let expected_label = expected[node_id].labels.get(label).unwrap();
+                assert_eq!(label.path, expected_label.path);
+            }
+        }
+
+        let contracted = contract_chains(&g, &[5]);
+        assert_eq!(contracted.graph.node_count(), 4);
+        let start = contracted.contracted_node(5).unwrap();
+        let bags = contracted.unpack_bags(&run(&contracted.graph, start));
+        let expected = run(&g, 5);
+        for (node_id, bag) in bags.iter() {
+            assert_eq!(bag, &expected[node_id]);
+        }
+    }
+}
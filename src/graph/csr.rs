@@ -0,0 +1,240 @@
+use std::error::Error;
+
+use petgraph::graph::EdgeIndex;
+use petgraph::visit::EdgeRef;
+use petgraph::{Directed, Graph};
+
+use super::{OutEdge, SearchGraph};
+use crate::bag::{EdgeWeights, NodeId, Weight, WeightsTuple};
+
+/// A read-only graph in compressed sparse row format.
+///
+/// The outgoing edges of a node are stored contiguously, and the weights of all edges in flat
+/// arrays with a fixed number of weights per edge, instead of two vectors per edge. Columns that
+/// are unused by every edge, e.g. the attributes or the transfer weights, take no memory.
+///
+/// Edges are ordered by their source node, keeping the input order among edges of the same
+/// source. The `EdgeIndex` of an edge is its position in this order, so it may differ from the
+/// index of the same edge in the graph it was built from.
+#[derive(Debug, Clone)]
+pub struct CsrGraph<T> {
+    categories: Vec<Vec<T>>,
+    // the outgoing edges of node u are offsets[u]..offsets[u + 1]
+    offsets: Vec<usize>,
+    targets: Vec<NodeId>,
+    // the sources of the incoming edges of node v are
+    // reverse_sources[reverse_offsets[v]..reverse_offsets[v + 1]]
+    reverse_offsets: Vec<usize>,
+    reverse_sources: Vec<NodeId>,
+    weight_length: usize,
+    hidden_weight_length: usize,
+    weights: Vec<Weight>,
+    hidden_weights: Vec<Weight>,
+    // the following are empty if no edge uses them
+    attributes: Vec<u64>,
+    modes: Vec<u32>,
+    lines: Vec<u32>,
+    transfer_weights: Vec<Weight>,
+    wait_weights: Vec<Weight>,
+}
+
+impl<T> CsrGraph<T> {
+    /// Builds a graph with one node per entry of `categories` from the edges `(u, v, weights)`.
+    ///
+    /// All edges must have the same number of weights and of hidden weights. Transfer and wait
+    /// weights may be missing on some edges, which is the same as zeros.
+    pub fn from_edges(
+        categories: Vec<Vec<T>>,
+        mut edges: Vec<(NodeId, NodeId, WeightsTuple)>,
+    ) -> Result<CsrGraph<T>, Box<dyn Error>> {
+        let node_count = categories.len();
+        let weight_length = edges.first().map_or(0, |(_, _, w)| w.weights.len());
+        let hidden_weight_length = edges.first().map_or(0, |(_, _, w)| w.hidden_weights.len());
+        for (u, v, weight) in edges.iter() {
+            if *u >= node_count || *v >= node_count {
+                return Err(format!("Edge ({}, {}) has an unknown node", u, v).into());
+            }
+            if weight.weights.len() != weight_length {
+                return Err("Graph has inconsistent edge weights".into());
+            }
+            if weight.hidden_weights.len() != hidden_weight_length {
+                return Err("Graph has inconsistent hidden edge weights".into());
+            }
+            if ![0, weight_length].contains(&weight.transfer_weights.len()) {
+                return Err("Graph has inconsistent transfer weights".into());
+            }
+            if ![0, weight_length].contains(&weight.wait_weights.len()) {
+                return Err("Graph has inconsistent wait weights".into());
+            }
+        }
+        edges.sort_by_key(|(u, _, _)| *u);
+
+        let mut offsets = vec![0; node_count + 1];
+        let mut reverse_offsets = vec![0; node_count + 1];
+        for (u, v, _) in edges.iter() {
+            offsets[u + 1] += 1;
+            reverse_offsets[v + 1] += 1;
+        }
+        for node in 0..node_count {
+            offsets[node + 1] += offsets[node];
+            reverse_offsets[node + 1] += reverse_offsets[node];
+        }
+        let mut reverse_sources = vec![0; edges.len()];
+        let mut next_incoming = reverse_offsets.clone();
+        for (u, v, _) in edges.iter() {
+            reverse_sources[next_incoming[*v]] = *u;
+            next_incoming[*v] += 1;
+        }
+
+        let has_attributes = edges.iter().any(|(_, _, w)| w.attributes != 0);
+        let has_modes = edges.iter().any(|(_, _, w)| w.mode != 0);
+        let has_lines = edges.iter().any(|(_, _, w)| w.line != 0);
+        let has_transfer_weights = edges.iter().any(|(_, _, w)| !w.transfer_weights.is_empty());
+        let has_wait_weights = edges.iter().any(|(_, _, w)| !w.wait_weights.is_empty());
+        let mut graph = CsrGraph {
+            categories,
+            offsets,
+            targets: Vec::with_capacity(edges.len()),
+            reverse_offsets,
+            reverse_sources,
+            weight_length,
+            hidden_weight_length,
+            weights: Vec::with_capacity(edges.len() * weight_length),
+            hidden_weights: Vec::with_capacity(edges.len() * hidden_weight_length),
+            attributes: vec![],
+            modes: vec![],
+            lines: vec![],
+            transfer_weights: vec![],
+            wait_weights: vec![],
+        };
+        let zeros = vec![0; weight_length];
+        for (_, v, weight) in edges {
+            graph.targets.push(v);
+            graph.weights.extend(weight.weights);
+            graph.hidden_weights.extend(weight.hidden_weights);
+            if has_attributes {
+                graph.attributes.push(weight.attributes);
+            }
+            if has_modes {
+                graph.modes.push(weight.mode);
+            }
+            if has_lines {
+                graph.lines.push(weight.line);
+            }
+            if has_transfer_weights {
+                graph
+                    .transfer_weights
+                    .extend(if weight.transfer_weights.is_empty() {
+                        &zeros
+                    } else {
+                        &weight.transfer_weights
+                    });
+            }
+            if has_wait_weights {
+                graph
+                    .wait_weights
+                    .extend(if weight.wait_weights.is_empty() {
+                        &zeros
+                    } else {
+                        &weight.wait_weights
+                    });
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Builds a graph with the same nodes, categories and edges as the petgraph graph.
+    pub fn from_graph(
+        g: &Graph<Vec<T>, WeightsTuple, Directed>,
+    ) -> Result<CsrGraph<T>, Box<dyn Error>>
+    where
+        T: Clone,
+    {
+        let categories = g.node_weights().cloned().collect();
+        let edges = g
+            .edge_references()
+            .map(|edge| {
+                (
+                    edge.source().index(),
+                    edge.target().index(),
+                    edge.weight().clone(),
+                )
+            })
+            .collect();
+        CsrGraph::from_edges(categories, edges)
+    }
+
+    fn weights_of(&self, edge: usize) -> EdgeWeights<'_> {
+        EdgeWeights {
+            weights: slice(&self.weights, edge, self.weight_length),
+            hidden_weights: slice(&self.hidden_weights, edge, self.hidden_weight_length),
+            attributes: self.attributes.get(edge).copied().unwrap_or(0),
+            mode: self.modes.get(edge).copied().unwrap_or(0),
+            line: self.lines.get(edge).copied().unwrap_or(0),
+            transfer_weights: slice(&self.transfer_weights, edge, self.weight_length),
+            wait_weights: slice(&self.wait_weights, edge, self.weight_length),
+        }
+    }
+}
+
+// returns the weights of the edge in a flat array, or an empty slice if the array is unused
+fn slice(values: &[Weight], edge: usize, length: usize) -> &[Weight] {
+    values
+        .get(edge * length..(edge + 1) * length)
+        .unwrap_or(&[])
+}
+
+impl<T> SearchGraph<T> for CsrGraph<T> {
+    fn node_count(&self) -> usize {
+        self.categories.len()
+    }
+
+    fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    fn categories(&self, node: NodeId) -> Option<&[T]> {
+        self.categories
+            .get(node)
+            .map(|categories| categories.as_slice())
+    }
+
+    fn out_edges(&self, node: NodeId) -> impl Iterator<Item = OutEdge<'_>> {
+        let edges = if node < self.categories.len() {
+            self.offsets[node]..self.offsets[node + 1]
+        } else {
+            0..0
+        };
+        edges.map(|edge| OutEdge {
+            id: EdgeIndex::new(edge),
+            target: self.targets[edge],
+            weights: self.weights_of(edge),
+        })
+    }
+
+    fn predecessors(&self, node: NodeId) -> impl Iterator<Item = NodeId> {
+        let sources = if node < self.categories.len() {
+            &self.reverse_sources[self.reverse_offsets[node]..self.reverse_offsets[node + 1]]
+        } else {
+            &[]
+        };
+        sources.iter().copied()
+    }
+
+    fn edge_endpoints(&self, edge: EdgeIndex) -> Option<(NodeId, NodeId)> {
+        let target = *self.targets.get(edge.index())?;
+        let source = self
+            .offsets
+            .partition_point(|offset| *offset <= edge.index())
+            - 1;
+        Some((source, target))
+    }
+
+    fn edge_weights(&self, edge: EdgeIndex) -> Option<EdgeWeights<'_>> {
+        if edge.index() < self.targets.len() {
+            Some(self.weights_of(edge.index()))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,417 @@
+mod test;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::error::Error;
+
+use super::SearchGraph;
+use crate::bag::{Bag, Label, NodeId, Weight};
+use crate::mlc::MLCError;
+
+// number of labels a witness search may settle before it gives up
+const WITNESS_SEARCH_LIMIT: usize = 1000;
+
+type ArcId = usize;
+
+// values, hidden values and arcs of a label of the upward searches
+type UpwardLabel = (Vec<Weight>, Vec<Weight>, Vec<ArcId>);
+
+/// An edge of the hierarchy, either an edge of the graph or a shortcut for two arcs.
+#[derive(Debug, Clone)]
+struct Arc {
+    source: NodeId,
+    target: NodeId,
+    weights: Vec<Weight>,
+    hidden_weights: Vec<Weight>,
+    children: Option<(ArcId, ArcId)>,
+}
+
+/// A multi-criteria contraction hierarchy for repeated point-to-point queries on a static
+/// graph.
+///
+/// Nodes are contracted one by one. When a node is contracted, a shortcut is added for every
+/// Pareto-optimal combination of an incoming and an outgoing arc, unless a witness search finds
+/// a path between the neighbours around the node that weakly dominates it. Parallel arcs are
+/// kept as Pareto sets. `query` then only needs to search upwards from both ends.
+///
+/// Attributes, modes, lines, categories and limits are not supported; graphs with transfer or
+/// wait weights are rejected.
+#[derive(Debug, Clone)]
+pub struct ContractionHierarchy {
+    arcs: Vec<Arc>,
+    rank: Vec<usize>,
+    // arcs to higher ranked nodes, by source
+    upward: Vec<Vec<ArcId>>,
+    // arcs from higher ranked nodes, by target
+    downward: Vec<Vec<ArcId>>,
+    weight_length: usize,
+    hidden_weight_length: usize,
+}
+
+impl ContractionHierarchy {
+    /// Contracts all nodes of the graph.
+    pub fn build<T, G: SearchGraph<T>>(g: &G) -> Result<ContractionHierarchy, Box<dyn Error>> {
+        let node_count = g.node_count();
+        let mut builder = Builder {
+            arcs: vec![],
+            outgoing: vec![vec![]; node_count],
+            incoming: vec![vec![]; node_count],
+            contracted: vec![false; node_count],
+            contracted_neighbours: vec![0; node_count],
+        };
+        let mut weight_length = None;
+        let mut hidden_weight_length = 0;
+        for node in 0..node_count {
+            for edge in g.out_edges(node) {
+                let weights = edge.weights;
+                if !weights.transfer_weights.is_empty() || !weights.wait_weights.is_empty() {
+                    return Err(
+                        "Contraction hierarchies do not support transfer or wait weights".into(),
+                    );
+                }
+                if *weight_length.get_or_insert(weights.weights.len()) != weights.weights.len() {
+                    return Err("Graph has inconsistent edge weights".into());
+                }
+                hidden_weight_length = weights.hidden_weights.len();
+                if node != edge.target {
+                    builder.add_arc(Arc {
+                        source: node,
+                        target: edge.target,
+                        weights: weights.weights.to_vec(),
+                        hidden_weights: weights.hidden_weights.to_vec(),
+                        children: None,
+                    });
+                }
+            }
+        }
+
+        let mut rank = vec![0; node_count];
+        let mut queue = (0..node_count)
+            .map(|node| Reverse((builder.priority(node), node)))
+            .collect::<BinaryHeap<_>>();
+        let mut next_rank = 0;
+        while let Some(Reverse((priority, node))) = queue.pop() {
+            // the priority may have changed since the node was pushed
+            let current_priority = builder.priority(node);
+            if current_priority > priority {
+                if let Some(Reverse((next_priority, _))) = queue.peek() {
+                    if current_priority > *next_priority {
+                        queue.push(Reverse((current_priority, node)));
+                        continue;
+                    }
+                }
+            }
+            builder.contract(node);
+            rank[node] = next_rank;
+            next_rank += 1;
+        }
+
+        let mut upward = vec![vec![]; node_count];
+        let mut downward = vec![vec![]; node_count];
+        for node in 0..node_count {
+            for arc_id in builder.outgoing[node].iter() {
+                let arc = &builder.arcs[*arc_id];
+                if rank[arc.target] > rank[arc.source] {
+                    upward[arc.source].push(*arc_id);
+                } else {
+                    downward[arc.target].push(*arc_id);
+                }
+            }
+        }
+
+        Ok(ContractionHierarchy {
+            arcs: builder.arcs,
+            rank,
+            upward,
+            downward,
+            weight_length: weight_length.unwrap_or(0),
+            hidden_weight_length,
+        })
+    }
+
+    /// Returns the number of shortcuts that were added.
+    pub fn shortcut_count(&self) -> usize {
+        self.arcs
+            .iter()
+            .filter(|arc| arc.children.is_some())
+            .count()
+    }
+
+    /// Returns the Pareto front of the paths from `start` to `target`, i.e. the same labels as
+    /// the bag of `target` after `MLC::run` from `start`, up to paths of equal values.
+    pub fn query(&self, start: NodeId, target: NodeId) -> Result<Bag<NodeId>, MLCError> {
+        for node in [start, target] {
+            if node >= self.rank.len() {
+                return Err(MLCError::UnknownNodeId(node));
+            }
+        }
+        let forward = self.upward_search(start, &self.upward, |arc| arc.target);
+        let backward = self.upward_search(target, &self.downward, |arc| arc.source);
+
+        // dominance is checked with the arcs as paths, only the front is unpacked into nodes
+        let mut packed_front = Bag::new_empty();
+        for (node, forward_labels) in forward.iter() {
+            let Some(backward_labels) = backward.get(node) else {
+                continue;
+            };
+            for (forward_values, forward_hidden, forward_arcs) in forward_labels {
+                for (backward_values, backward_hidden, backward_arcs) in backward_labels {
+                    let arcs = forward_arcs
+                        .iter()
+                        .chain(backward_arcs.iter().rev())
+                        .copied()
+                        .collect();
+                    packed_front.add_if_necessary(Label {
+                        values: sum(forward_values, backward_values),
+                        hidden_values: sum(forward_hidden, backward_hidden),
+                        path: arcs,
+                        node_id: target,
+                        state: 0,
+                        line: None,
+                        edge: None,
+                    });
+                }
+            }
+        }
+
+        let labels = packed_front
+            .labels
+            .into_iter()
+            .map(|label| {
+                let mut path = vec![start];
+                for arc in label.path.iter() {
+                    self.unpack(*arc, &mut path);
+                }
+                Label { path, ..label }
+            })
+            .collect();
+        Ok(Bag { labels })
+    }
+
+    // multi-criteria search from the node along the given arcs, returns the Pareto set of
+    // values, hidden values and arcs at every reached node
+    fn upward_search(
+        &self,
+        start: NodeId,
+        arcs: &[Vec<ArcId>],
+        next: impl Fn(&Arc) -> NodeId,
+    ) -> HashMap<NodeId, Vec<UpwardLabel>> {
+        let mut labels: Vec<UpwardLabel> = vec![(
+            vec![0; self.weight_length],
+            vec![0; self.hidden_weight_length],
+            vec![],
+        )];
+        let mut bags: HashMap<NodeId, Vec<usize>> = HashMap::from([(start, vec![0])]);
+        let mut queue = BinaryHeap::from([Reverse((labels[0].0.clone(), start, 0))]);
+        while let Some(Reverse((_, node, label_id))) = queue.pop() {
+            if !bags[&node].contains(&label_id) {
+                continue;
+            }
+            for arc_id in arcs[node].iter() {
+                let arc = &self.arcs[*arc_id];
+                let (values, hidden_values, path) = &labels[label_id];
+                let values = sum(values, &arc.weights);
+                let hidden_values = sum(hidden_values, &arc.hidden_weights);
+                let mut path = path.clone();
+                path.push(*arc_id);
+
+                let next_node = next(arc);
+                let bag = bags.entry(next_node).or_default();
+                if bag
+                    .iter()
+                    .any(|other| dominates(&labels[*other].0, &values))
+                {
+                    continue;
+                }
+                bag.retain(|other| !dominates(&values, &labels[*other].0));
+                bag.push(labels.len());
+                queue.push(Reverse((values.clone(), next_node, labels.len())));
+                labels.push((values, hidden_values, path));
+            }
+        }
+        bags.into_iter()
+            .map(|(node, bag)| {
+                let bag = bag.into_iter().map(|id| labels[id].clone()).collect();
+                (node, bag)
+            })
+            .collect()
+    }
+
+    // appends the nodes of the arc to the path, except its source
+    fn unpack(&self, arc_id: ArcId, path: &mut Vec<NodeId>) {
+        let arc = &self.arcs[arc_id];
+        match arc.children {
+            Some((first, second)) => {
+                self.unpack(first, path);
+                self.unpack(second, path);
+            }
+            None => path.push(arc.target),
+        }
+    }
+}
+
+struct Builder {
+    arcs: Vec<Arc>,
+    // arcs that are not dominated by a parallel arc
+    outgoing: Vec<Vec<ArcId>>,
+    incoming: Vec<Vec<ArcId>>,
+    contracted: Vec<bool>,
+    contracted_neighbours: Vec<i64>,
+}
+
+impl Builder {
+    // adds the arc unless a parallel arc weakly dominates it, and removes the parallel arcs it
+    // dominates
+    fn add_arc(&mut self, arc: Arc) {
+        let parallel = self.outgoing[arc.source]
+            .iter()
+            .copied()
+            .filter(|id| self.arcs[*id].target == arc.target)
+            .collect::<Vec<_>>();
+        if parallel
+            .iter()
+            .any(|id| dominates(&self.arcs[*id].weights, &arc.weights))
+        {
+            return;
+        }
+        let dominated = parallel
+            .into_iter()
+            .filter(|id| dominates(&arc.weights, &self.arcs[*id].weights))
+            .collect::<HashSet<_>>();
+        self.outgoing[arc.source].retain(|id| !dominated.contains(id));
+        self.incoming[arc.target].retain(|id| !dominated.contains(id));
+
+        let id = self.arcs.len();
+        self.outgoing[arc.source].push(id);
+        self.incoming[arc.target].push(id);
+        self.arcs.push(arc);
+    }
+
+    fn neighbours(&self, arcs: &[ArcId], endpoint: impl Fn(&Arc) -> NodeId) -> HashSet<NodeId> {
+        arcs.iter()
+            .map(|id| endpoint(&self.arcs[*id]))
+            .filter(|node| !self.contracted[*node])
+            .collect()
+    }
+
+    fn priority(&self, node: NodeId) -> i64 {
+        let n_in = self
+            .neighbours(&self.incoming[node], |arc| arc.source)
+            .len() as i64;
+        let n_out = self
+            .neighbours(&self.outgoing[node], |arc| arc.target)
+            .len() as i64;
+        n_in * n_out - n_in - n_out + self.contracted_neighbours[node]
+    }
+
+    fn contract(&mut self, node: NodeId) {
+        let active = |arcs: &Vec<ArcId>, endpoint: fn(&Arc) -> NodeId| {
+            arcs.iter()
+                .copied()
+                .filter(|id| !self.contracted[endpoint(&self.arcs[*id])])
+                .collect::<Vec<_>>()
+        };
+        let incoming = active(&self.incoming[node], |arc| arc.source);
+        let outgoing = active(&self.outgoing[node], |arc| arc.target);
+
+        // candidate shortcuts by their source
+        let mut candidates: HashMap<NodeId, Vec<(ArcId, ArcId, Vec<Weight>)>> = HashMap::new();
+        for first in incoming.iter() {
+            for second in outgoing.iter() {
+                let (a, b) = (&self.arcs[*first], &self.arcs[*second]);
+                if a.source != b.target {
+                    candidates.entry(a.source).or_default().push((
+                        *first,
+                        *second,
+                        sum(&a.weights, &b.weights),
+                    ));
+                }
+            }
+        }
+
+        self.contracted[node] = true;
+        for (source, candidates) in candidates {
+            let fronts = self.witness_search(source, &candidates);
+            for (first, second, weights) in candidates {
+                let target = self.arcs[second].target;
+                let witnessed = fronts
+                    .get(&target)
+                    .is_some_and(|front| front.iter().any(|values| dominates(values, &weights)));
+                if !witnessed {
+                    let hidden_weights = sum(
+                        &self.arcs[first].hidden_weights,
+                        &self.arcs[second].hidden_weights,
+                    );
+                    self.add_arc(Arc {
+                        source,
+                        target,
+                        weights,
+                        hidden_weights,
+                        children: Some((first, second)),
+                    });
+                }
+            }
+        }
+        for neighbour in self
+            .neighbours(&incoming, |arc| arc.source)
+            .union(&self.neighbours(&outgoing, |arc| arc.target))
+        {
+            self.contracted_neighbours[*neighbour] += 1;
+        }
+    }
+
+    // Pareto fronts of the paths from the source over nodes that are not contracted yet. Only
+    // paths that could dominate a candidate are followed, and the search gives up after a
+    // fixed number of labels, which only leads to unnecessary shortcuts.
+    fn witness_search(
+        &self,
+        source: NodeId,
+        candidates: &[(ArcId, ArcId, Vec<Weight>)],
+    ) -> HashMap<NodeId, Vec<Vec<Weight>>> {
+        let weight_length = candidates[0].2.len();
+        let bound = (0..weight_length)
+            .map(|i| candidates.iter().map(|c| c.2[i]).max().unwrap())
+            .collect::<Vec<_>>();
+
+        let mut fronts: HashMap<NodeId, Vec<Vec<Weight>>> =
+            HashMap::from([(source, vec![vec![0; weight_length]])]);
+        let mut queue = BinaryHeap::from([Reverse((vec![0; weight_length], source))]);
+        let mut settled = 0;
+        while let Some(Reverse((values, node))) = queue.pop() {
+            if !fronts[&node].contains(&values) {
+                continue;
+            }
+            settled += 1;
+            if settled > WITNESS_SEARCH_LIMIT {
+                break;
+            }
+            for arc_id in self.outgoing[node].iter() {
+                let arc = &self.arcs[*arc_id];
+                if self.contracted[arc.target] {
+                    continue;
+                }
+                let values = sum(&values, &arc.weights);
+                if !dominates(&values, &bound) {
+                    continue;
+                }
+                let front = fronts.entry(arc.target).or_default();
+                if front.iter().any(|other| dominates(other, &values)) {
+                    continue;
+                }
+                front.retain(|other| !dominates(&values, other));
+                front.push(values.clone());
+                queue.push(Reverse((values, arc.target)));
+            }
+        }
+        fronts
+    }
+}
+
+// returns true if a is at most b in every value
+fn dominates(a: &[Weight], b: &[Weight]) -> bool {
+    a.iter().zip(b.iter()).all(|(a, b)| a <= b)
+}
+
+fn sum(a: &[Weight], b: &[Weight]) -> Vec<Weight> {
+    a.iter().zip(b.iter()).map(|(a, b)| a + b).collect()
+}
@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::NodeIndex;
+
+    use crate::bag::{Bag, WeightsTuple};
+    use crate::graph::hierarchy::ContractionHierarchy;
+    use crate::graph::CsrGraph;
+    use crate::mlc::MLC;
+    use crate::read::{self, MLCGraph};
+    use crate::test_util::Lcg;
+
+    fn random_graph(rng: &mut Lcg, n_nodes: usize, n_edges: usize) -> MLCGraph<()> {
+        let mut g = MLCGraph::<()>::new();
+        for _ in 0..n_nodes {
+            g.add_node(vec![]);
+        }
+        for i in 0..n_edges {
+            let u = if i < n_nodes {
+                i
+            } else {
+                rng.next(n_nodes as u64) as usize
+            };
+            let v = rng.next(n_nodes as u64) as usize;
+            let weights = WeightsTuple {
+                weights: vec![rng.next(10), rng.next(10), rng.next(3)],
+                hidden_weights: vec![rng.next(5)],
+                ..Default::default()
+            };
+            g.add_edge(NodeIndex::new(u), NodeIndex::new(v), weights);
+        }
+        g
+    }
+
+    fn values(bag: &Bag<usize>) -> Vec<Vec<u64>> {
+        let mut values = bag
+            .labels
+            .iter()
+            .map(|label| label.values.clone())
+            .collect::<Vec<_>>();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn test_query_matches_run() {
+        let mut rng = Lcg(11);
+        for _ in 0..5 {
+            let g = random_graph(&mut rng, 25, 70);
+            let ch = ContractionHierarchy::build(&g).unwrap();
+            for start in 0..g.node_count() {
+                let mut mlc = MLC::new(&g).unwrap();
+                mlc.set_start_node(start);
+                let bags = mlc.run().unwrap();
+                for target in 0..g.node_count() {
+                    let front = ch.query(start, target).unwrap();
+                    let expected = bags.get(&target).map(values).unwrap_or_default();
+                    assert_eq!(values(&front), expected, "{} -> {}", start, target);
+
+                    for label in front.labels.iter() {
+                        assert_eq!(label.path.first(), Some(&start));
+                        assert_eq!(label.path.last(), Some(&target));
+                        for step in label.path.windows(2) {
+                            let edge =
+                                g.find_edge(NodeIndex::new(step[0]), NodeIndex::new(step[1]));
+                            assert!(edge.is_some());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_on_csr_graph() {
+        let g = read::read_csr_graph_with_int_ids("testdata/edges.csv").unwrap();
+        let ch = ContractionHierarchy::build(&g).unwrap();
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        for (node_id, bag) in bags.iter() {
+            assert_eq!(values(&ch.query(0, *node_id).unwrap()), values(bag));
+        }
+        assert!(ch.query(0, 100).is_err());
+
+        let g = read::read_graph_with_int_ids("testdata/edges_transfers.csv").unwrap();
+        assert!(ContractionHierarchy::build(&CsrGraph::from_graph(&g).unwrap()).is_err());
+    }
+}
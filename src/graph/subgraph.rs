@@ -0,0 +1,120 @@
+mod test;
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::error::Error;
+
+use bimap::BiMap;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+
+use crate::bag::{NodeId, Weight};
+use crate::read::MLCGraph;
+
+/// A part of a graph with re-indexed nodes, see `extract_reachable`.
+pub struct Subgraph<T> {
+    pub graph: MLCGraph<T>,
+    /// Maps the external node ids to the node ids of the subgraph.
+    pub node_map: BiMap<String, usize>,
+}
+
+/// Extracts the part of the graph that is reachable from `start` within the given bounds, one
+/// per weight.
+///
+/// A node is kept if, for every weight, its shortest distance from `start` is at most the bound,
+/// and an edge if its source is kept and the distance of the source plus the edge weight is
+/// within every bound. So every path from `start` whose values are within the bounds is kept,
+/// and runs from `start` yield the same labels within the bounds as on the whole graph.
+///
+/// Nodes are re-indexed in the order of their old ids, starting with 0. The external ids are
+/// taken from `node_map`, or are the old ids if it is `None`. Use `read::write_graph` to write
+/// the subgraph.
+pub fn extract_reachable<T: Clone>(
+    g: &MLCGraph<T>,
+    node_map: Option<&BiMap<String, usize>>,
+    start: NodeId,
+    bounds: &[Weight],
+) -> Result<Subgraph<T>, Box<dyn Error>> {
+    if start >= g.node_count() {
+        return Err(format!("Unknown start node: {}", start).into());
+    }
+    if let Some(edge) = g.edge_references().next() {
+        if edge.weight().weights.len() != bounds.len() {
+            return Err(format!(
+                "Expected {} bounds, got {}",
+                edge.weight().weights.len(),
+                bounds.len()
+            )
+            .into());
+        }
+    }
+
+    let distances = (0..bounds.len())
+        .map(|criterion| shortest_distances(g, start, criterion, bounds[criterion]))
+        .collect::<Vec<_>>();
+    let within_bounds = |node: usize, weights: Option<&[Weight]>| {
+        distances.iter().enumerate().all(|(criterion, distance)| {
+            let weight = weights.map_or(0, |weights| weights[criterion]);
+            distance[node].is_some_and(|d| d.saturating_add(weight) <= bounds[criterion])
+        })
+    };
+
+    let mut new_ids = vec![None; g.node_count()];
+    let mut graph = MLCGraph::new();
+    let mut new_node_map = BiMap::new();
+    for node in g.node_indices() {
+        if !within_bounds(node.index(), None) {
+            continue;
+        }
+        let new_id = graph.add_node(g[node].clone());
+        new_ids[node.index()] = Some(new_id);
+        let external_id = match node_map {
+            Some(node_map) => node_map
+                .get_by_right(&node.index())
+                .ok_or(format!("Node {} is not in the node map", node.index()))?
+                .clone(),
+            None => node.index().to_string(),
+        };
+        new_node_map.insert(external_id, new_id.index());
+    }
+    for edge in g.edge_references() {
+        let (Some(u), Some(v)) = (
+            new_ids[edge.source().index()],
+            new_ids[edge.target().index()],
+        ) else {
+            continue;
+        };
+        if within_bounds(edge.source().index(), Some(&edge.weight().weights)) {
+            graph.add_edge(u, v, edge.weight().clone());
+        }
+    }
+
+    Ok(Subgraph {
+        graph,
+        node_map: new_node_map,
+    })
+}
+
+// Dijkstra on a single weight, stopping at the bound
+fn shortest_distances<T>(
+    g: &MLCGraph<T>,
+    start: NodeId,
+    criterion: usize,
+    bound: Weight,
+) -> Vec<Option<Weight>> {
+    let mut distances = vec![None; g.node_count()];
+    let mut queue = BinaryHeap::from([Reverse((0 as Weight, start))]);
+    while let Some(Reverse((distance, node))) = queue.pop() {
+        if distances[node].is_some() {
+            continue;
+        }
+        distances[node] = Some(distance);
+        for edge in g.edges(NodeIndex::new(node)) {
+            let next_distance = distance.saturating_add(edge.weight().weights[criterion]);
+            if next_distance <= bound && distances[edge.target().index()].is_none() {
+                queue.push(Reverse((next_distance, edge.target().index())));
+            }
+        }
+    }
+    distances
+}
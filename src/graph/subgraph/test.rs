@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use bimap::BiMap;
+    use petgraph::visit::EdgeRef;
+
+    use crate::bag::WeightsTuple;
+    use crate::graph::subgraph::extract_reachable;
+    use crate::mlc::MLC;
+    use crate::read;
+    use crate::read::MLCGraph;
+
+    fn weights(weights: Vec<u64>) -> WeightsTuple {
+        WeightsTuple {
+            weights,
+            ..Default::default()
+        }
+    }
+
+    // nodes a to e, where d is too far by the first weight and e by the second one
+    fn graph() -> (MLCGraph<()>, BiMap<String, usize>) {
+        let mut g = MLCGraph::new();
+        let mut node_map = BiMap::new();
+        let nodes = ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|name| {
+                let node = g.add_node(vec![]);
+                node_map.insert(name.to_string(), node.index());
+                node
+            })
+            .collect::<Vec<_>>();
+        for (u, v, w) in [
+            (0, 1, vec![1, 1]),
+            (1, 2, vec![1, 1]),
+            (0, 2, vec![3, 0]),
+            (2, 3, vec![5, 5]),
+            (3, 0, vec![1, 1]),
+            (1, 4, vec![0, 4]),
+        ] {
+            g.add_edge(nodes[u], nodes[v], weights(w));
+        }
+        (g, node_map)
+    }
+
+    // the labels within the bounds, with values and the path of external ids
+    fn labels(
+        g: &MLCGraph<()>,
+        node_map: &BiMap<String, usize>,
+        start: &str,
+        bounds: &[u64],
+    ) -> Vec<(Vec<u64>, Vec<String>)> {
+        let mut mlc = MLC::new(g).unwrap();
+        mlc.set_start_node(*node_map.get_by_left(start).unwrap());
+        let mut labels = mlc
+            .run()
+            .unwrap()
+            .values()
+            .flat_map(|bag| bag.labels.iter())
+            .filter(|label| label.values.iter().zip(bounds).all(|(v, b)| v <= b))
+            .map(|label| {
+                let path = label
+                    .path
+                    .iter()
+                    .map(|node| node_map.get_by_right(node).unwrap().clone())
+                    .collect();
+                (label.values.clone(), path)
+            })
+            .collect::<Vec<_>>();
+        labels.sort();
+        labels
+    }
+
+    #[test]
+    fn test_extract_reachable() {
+        let (g, node_map) = graph();
+        let subgraph = extract_reachable(&g, Some(&node_map), 0, &[3, 3]).unwrap();
+        assert_eq!(subgraph.graph.node_count(), 3);
+        assert_eq!(subgraph.graph.edge_count(), 3);
+        for (name, id) in [("a", 0), ("b", 1), ("c", 2)] {
+            assert_eq!(subgraph.node_map.get_by_left(name), Some(&id));
+        }
+        assert_eq!(
+            labels(&subgraph.graph, &subgraph.node_map, "a", &[3, 3]),
+            labels(&g, &node_map, "a", &[3, 3])
+        );
+
+        // without a node map, the external ids are the old ids
+        let subgraph = extract_reachable(&g, None, 1, &[10, 1]).unwrap();
+        assert_eq!(subgraph.graph.node_count(), 2);
+        assert_eq!(subgraph.node_map.get_by_left("1"), Some(&0));
+        assert_eq!(subgraph.node_map.get_by_left("2"), Some(&1));
+
+        assert!(extract_reachable(&g, None, 5, &[3, 3]).is_err());
+        assert!(extract_reachable(&g, None, 0, &[3]).is_err());
+    }
+
+    #[test]
+    fn test_write_graph() {
+        let (g, node_map) = graph();
+        let subgraph = extract_reachable(&g, Some(&node_map), 0, &[3, 3]).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("mlc_test_write_graph_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        read::write_graph(&subgraph.graph, Some(&subgraph.node_map), path).unwrap();
+        let (read_graph, read_node_map) = read::read_graph_and_reset_ids(path).unwrap();
+        let edges = |g: &MLCGraph<()>, node_map: &BiMap<String, usize>| {
+            let mut edges = g
+                .edge_references()
+                .map(|edge| {
+                    (
+                        node_map
+                            .get_by_right(&edge.source().index())
+                            .unwrap()
+                            .clone(),
+                        node_map
+                            .get_by_right(&edge.target().index())
+                            .unwrap()
+                            .clone(),
+                        edge.weight().clone(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            edges.sort_by(|a, b| (&a.0, &a.1, &a.2.weights).cmp(&(&b.0, &b.1, &b.2.weights)));
+            edges
+        };
+        assert_eq!(
+            edges(&read_graph, &read_node_map),
+            edges(&subgraph.graph, &subgraph.node_map)
+        );
+
+        // optional columns are written if used, and read back as written
+        let mut g = MLCGraph::new();
+        let (a, b) = (g.add_node(vec![()]), g.add_node(vec![]));
+        g.add_edge(
+            a,
+            b,
+            WeightsTuple {
+                weights: vec![1, 2],
+                mode: 3,
+                transfer_weights: vec![4, 5],
+                ..Default::default()
+            },
+        );
+        g.add_edge(b, a, weights(vec![6, 7]));
+        read::write_graph(&g, None, path).unwrap();
+        let read_graph = read::read_graph_with_int_ids(path).unwrap();
+        assert_eq!(
+            read_graph.edge_weights().collect::<Vec<_>>(),
+            g.edge_weights().collect::<Vec<_>>()
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
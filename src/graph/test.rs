@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::EdgeIndex;
+
+    use crate::bag::WeightsTuple;
+    use crate::graph::{CsrGraph, SearchGraph};
+    use crate::mlc;
+    use crate::read;
+    use crate::read::MLCGraph;
+
+    fn out_edges<G: SearchGraph<()>>(g: &G, node: usize) -> Vec<(usize, Vec<u64>, u64, u32)> {
+        let mut edges = g
+            .out_edges(node)
+            .map(|edge| {
+                (
+                    edge.target,
+                    edge.weights.weights.to_vec(),
+                    edge.weights.attributes,
+                    edge.weights.mode,
+                )
+            })
+            .collect::<Vec<_>>();
+        edges.sort();
+        edges
+    }
+
+    #[test]
+    fn test_csr_graph_matches_graph() {
+        for path in [
+            "testdata/edges.csv",
+            "testdata/edges_attributes.csv",
+            "testdata/edges_modes.csv",
+        ] {
+            let g: MLCGraph<()> = read::read_graph_with_int_ids(path).unwrap();
+            let csr = read::read_csr_graph_with_int_ids(path).unwrap();
+            assert_eq!(SearchGraph::node_count(&csr), g.node_count());
+            assert_eq!(SearchGraph::edge_count(&csr), g.edge_count());
+            for node in 0..g.node_count() + 1 {
+                assert_eq!(out_edges(&csr, node), out_edges(&g, node));
+                let mut predecessors = csr.predecessors(node).collect::<Vec<_>>();
+                let mut expected = SearchGraph::predecessors(&g, node).collect::<Vec<_>>();
+                predecessors.sort();
+                expected.sort();
+                assert_eq!(predecessors, expected);
+            }
+            for edge in csr.out_edges(1) {
+                assert_eq!(csr.edge_endpoints(edge.id), Some((1, edge.target)));
+            }
+            let n_edges = SearchGraph::edge_count(&csr);
+            assert_eq!(csr.edge_endpoints(EdgeIndex::new(n_edges)), None);
+        }
+    }
+
+    #[test]
+    fn test_csr_graph_sparse_columns() {
+        let weights = |weights: Vec<u64>, transfer_weights: Vec<u64>| WeightsTuple {
+            weights,
+            transfer_weights,
+            ..Default::default()
+        };
+        let csr = CsrGraph::from_edges(
+            vec![vec!["a"], vec![], vec!["b"]],
+            vec![
+                (2, 0, weights(vec![3, 3], vec![])),
+                (0, 1, weights(vec![1, 2], vec![5, 5])),
+                (0, 2, weights(vec![2, 1], vec![])),
+            ],
+        )
+        .unwrap();
+        assert_eq!(csr.categories(2), Some(&["b"][..]));
+        assert_eq!(csr.categories(3), None);
+        let edges = csr.out_edges(0).collect::<Vec<_>>();
+        assert_eq!(edges[0].weights.transfer_weights, &[5, 5]);
+        assert_eq!(edges[1].weights.transfer_weights, &[0, 0]);
+        assert_eq!(edges[1].weights.wait_weights, &[] as &[u64]);
+        assert_eq!(csr.edge_endpoints(EdgeIndex::new(2)), Some((2, 0)));
+
+        let inconsistent = CsrGraph::<()>::from_edges(
+            vec![vec![], vec![]],
+            vec![
+                (0, 1, weights(vec![1, 2], vec![])),
+                (1, 0, weights(vec![1], vec![])),
+            ],
+        );
+        assert!(inconsistent.is_err());
+        let unknown_node =
+            CsrGraph::<()>::from_edges(vec![vec![]], vec![(0, 1, weights(vec![1], vec![]))]);
+        assert!(unknown_node.is_err());
+    }
+
+    #[test]
+    fn test_run_mlc_on_csr_graph() {
+        let g = read::read_csr_graph_with_int_ids("testdata/edges.csv").unwrap();
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        let expected_result = mlc::read_bags("testdata/results.csv").unwrap();
+        assert!(bags == &expected_result);
+    }
+}
@@ -1,3 +1,12 @@
+pub mod analysis;
 pub mod bag;
+pub mod compression;
+pub mod diff;
+pub mod geojson;
+pub mod graph;
 pub mod mlc;
 pub mod read;
+pub mod render;
+#[cfg(test)]
+mod test_util;
+pub mod validate;
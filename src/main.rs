@@ -1,29 +1,134 @@
-use bag::WeightsTuple;
-use color_eyre::eyre::Result;
+use bag::Bag;
+use color_eyre::eyre::{eyre, Result};
 use log::info;
-use petgraph::dot::{Config, Dot};
-use petgraph::Graph;
-use std::error::Error;
-use std::fs::File;
-use std::io::prelude::*;
+use std::collections::HashSet;
 use std::time::Instant;
 
+pub mod analysis;
 pub mod bag;
+pub mod compression;
+pub mod diff;
+pub mod geojson;
+pub mod graph;
 pub mod mlc;
 pub mod read;
+pub mod render;
+#[cfg(test)]
+mod test_util;
+pub mod validate;
 
-#[allow(dead_code)]
-fn render_graph(g: &Graph<(), WeightsTuple>, path: &str) -> Result<(), Box<dyn Error>> {
-    // save to file
-    let mut file = File::create(path)?;
-    let dot = Dot::with_config(&g, &[Config::NodeNoLabel]);
-    file.write_all(format!("{:?}", dot).as_bytes())?;
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    match std::env::args().nth(1).as_deref() {
+        Some("inspect") => inspect()?,
+        Some("geojson") => export_geojson()?,
+        Some("render") => render()?,
+        Some("diff") => {
+            // exit code 1 is reserved for differences
+            if let Err(err) = diff() {
+                eprintln!("Error: {:?}", err);
+                std::process::exit(2);
+            }
+        }
+        _ => run_mlc(),
+    }
     Ok(())
 }
 
-fn main() -> Result<()> {
-    color_eyre::install()?;
-    run_mlc();
+// usage: mlc inspect <edges.csv> [start node]
+fn inspect() -> Result<()> {
+    let path = std::env::args()
+        .nth(2)
+        .ok_or_else(|| eyre!("usage: mlc inspect <edges.csv> [start node]"))?;
+    let start = std::env::args().nth(3);
+    let report =
+        validate::validate_file(&path, start.as_deref()).map_err(|err| eyre!(err.to_string()))?;
+    print!("{}", report);
+    Ok(())
+}
+
+// usage: mlc geojson <labels.csv> <coordinates.csv> <node id> <paths.geojson> [nodes.geojson]
+// Exports the labels of the node, and optionally all reached nodes, of a file written by
+// write_bags. Node ids are read as strings, so files with translated ids are supported.
+fn export_geojson() -> Result<()> {
+    let usage = "usage: mlc geojson <labels.csv> <coordinates.csv> <node id> <paths.geojson> \
+                 [nodes.geojson]";
+    let args = std::env::args().skip(2).collect::<Vec<_>>();
+    if args.len() < 4 {
+        return Err(eyre!(usage));
+    }
+    let node_id = &args[2];
+    let labels = mlc::read_labels::<String>(&args[0], &mlc::LabelsFormat::default())
+        .map_err(|err| eyre!(err.to_string()))?;
+    let mut bags: mlc::Bags<String> = mlc::Bags::new();
+    for label in labels {
+        bags.entry(label.node_id.clone())
+            .or_insert_with(|| Bag {
+                labels: HashSet::new(),
+            })
+            .labels
+            .insert(label);
+    }
+    let coordinates = geojson::read_coordinates(&args[1]).map_err(|err| eyre!(err.to_string()))?;
+    let bag = bags
+        .get(node_id)
+        .ok_or_else(|| eyre!("Node {} has no labels", node_id))?;
+    let paths =
+        geojson::paths_to_geojson(bag, &coordinates).map_err(|err| eyre!(err.to_string()))?;
+    geojson::write_geojson(&paths, &args[3]).map_err(|err| eyre!(err.to_string()))?;
+    if let Some(path) = args.get(4) {
+        let nodes = geojson::reached_nodes_to_geojson(&bags, &coordinates)
+            .map_err(|err| eyre!(err.to_string()))?;
+        geojson::write_geojson(&nodes, path).map_err(|err| eyre!(err.to_string()))?;
+    }
+    Ok(())
+}
+
+// usage: mlc render <edges.csv> <graph.dot|graph.svg> [start node] [target node]
+// Renders the graph, and if start and target are given, the Pareto paths from start to target.
+fn render() -> Result<()> {
+    let usage = "usage: mlc render <edges.csv> <graph.dot|graph.svg> [start node] [target node]";
+    let args = std::env::args().skip(2).collect::<Vec<_>>();
+    if args.len() != 2 && args.len() != 4 {
+        return Err(eyre!(usage));
+    }
+    let (g, node_map) =
+        read::read_graph_and_reset_ids(&args[0]).map_err(|err| eyre!(err.to_string()))?;
+    let bags = match args.get(2..4) {
+        Some([start, target]) => {
+            let node = |id: &String| {
+                node_map
+                    .get_by_left(id)
+                    .copied()
+                    .ok_or_else(|| eyre!("Unknown node id: {}", id))
+            };
+            let (start, target) = (node(start)?, node(target)?);
+            let mut mlc = mlc::MLC::new(&g).map_err(|err| eyre!(err.to_string()))?;
+            mlc.set_start_node(start);
+            let bags = mlc.run().map_err(|err| eyre!("{:?}", err))?;
+            Some(bags.get(&target).cloned().unwrap_or_else(Bag::new_empty))
+        }
+        _ => None,
+    };
+    let dot = render::render_dot(&g, Some(&node_map), bags.as_ref());
+    render::write_rendering(&dot, &args[1]).map_err(|err| eyre!(err.to_string()))?;
+    Ok(())
+}
+
+// usage: mlc diff <labels A.csv> <labels B.csv>
+// Prints the differences of two files written by write_bags as JSON and exits with 1 if they
+// differ and with 2 on errors, see diff::diff_bags.
+fn diff() -> Result<()> {
+    let usage = "usage: mlc diff <labels A.csv> <labels B.csv>";
+    let args = std::env::args().skip(2).collect::<Vec<_>>();
+    let [path_a, path_b] = args.as_slice() else {
+        return Err(eyre!(usage));
+    };
+    let diff = diff::diff_files(path_a, path_b).map_err(|err| eyre!(err.to_string()))?;
+    println!("{}", serde_json::to_string_pretty(&diff)?);
+    if !diff.is_empty() {
+        std::process::exit(1);
+    }
     Ok(())
 }
 
@@ -38,7 +143,8 @@ fn run_mlc() {
     #[allow(unused_mut)]
     let mut mlc = mlc::MLC::new(&g).unwrap();
     // mlc.set_update_label_func(update_label_func);
-    // mlc.set_debug(true);
+    // mlc.set_progress_observer(Box::new(mlc::progress::LogObserver));
+    // mlc.set_enable_timings(true);
     info!("Running MLC");
     let start = Instant::now();
     #[allow(unused_variables)]
@@ -46,5 +152,6 @@ fn run_mlc() {
     let bags = mlc.run().unwrap();
     let end = Instant::now();
     info!("MLC took {}ms", (end - start).as_millis());
-    mlc::write_bags(&bags, "data/labels.csv").unwrap();
+    mlc::write_bags(bags, "data/labels.csv").unwrap();
+    info!("MLC statistics: {:?}", mlc.statistics());
 }
@@ -1,37 +1,64 @@
 use crate::bag::*;
 use bimap::BiMap;
-use log::{debug, info};
-use petgraph::graph::NodeIndex;
-use petgraph::visit::EdgeRef;
+use log::{debug, warn};
+use petgraph::graph::EdgeIndex;
 use petgraph::{Directed, Graph};
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::error::Error;
 use std::fmt;
-use std::fmt::Display;
-use std::fs::{read_to_string, File};
-use std::hash::Hash;
+use std::fs::File;
 use std::io::Write;
-use std::num::ParseIntError;
-use std::str::FromStr;
 use std::time::Instant;
 
-use self::limit::Limits;
-
-mod limit;
+use crate::graph::SearchGraph;
+
+use self::automaton::Automaton;
+pub use self::labels::{
+    read_bags, read_bags_with_format, read_labels, write_bags, write_bags_with_format, LabelsFormat,
+};
+use self::limit::{Coverage, CoverageRequirement, Limit, Limits, Seeds};
+use self::progress::{Progress, ProgressObserver};
+use self::results::Results;
+use self::statistics::Statistics;
+use self::turns::TurnCosts;
+
+pub mod automaton;
+pub mod dynamic;
+pub mod labels;
+pub mod limit;
+pub mod progress;
+pub mod results;
+pub mod statistics;
 mod test;
+pub mod turns;
 
 type UpdateLabelFunc = fn(&Label<usize>, &Label<usize>) -> Label<usize>;
-
-pub struct MLC<'a, T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> {
+type EdgeFilter = Box<dyn Fn(EdgeIndex, &EdgeWeights) -> bool>;
+type NodeFilter = Box<dyn Fn(usize) -> bool>;
+
+pub struct MLC<
+    'a,
+    T: std::cmp::Eq + std::hash::Hash + std::marker::Copy,
+    G: SearchGraph<T> = Graph<Vec<T>, WeightsTuple, Directed>,
+> {
     // problem state
-    graph: &'a Graph<Vec<T>, WeightsTuple, Directed>,
+    graph: &'a G,
     update_label_func: Option<UpdateLabelFunc>,
 
     // config
     node_map: Option<BiMap<String, usize>>,
-    debug: bool,
+    progress_observer: Option<Box<dyn ProgressObserver>>,
+    progress_interval: usize,
     disable_paths: bool,
     enable_limit: bool,
+    enable_timings: bool,
+    limit_criteria: Vec<Criterion>,
+    edge_filter: Option<EdgeFilter>,
+    node_filter: Option<NodeFilter>,
+    required_attributes: u64,
+    forbidden_attributes: u64,
+    automaton: Option<Automaton>,
+    turn_costs: TurnCosts,
 
     // helper variables
     weight_length: usize,
@@ -39,8 +66,16 @@ pub struct MLC<'a, T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> {
 
     // internal state
     bags: Bags<usize>,
+    // the Pareto fronts of the bags after the last run, if the bags hold dominated labels of
+    // other lines or edges
+    fronts: Option<Bags<usize>>,
     queue: BinaryHeap<Label<usize>>,
     limits: Limits<T>,
+    statistics: Statistics,
+    // seeded limits per category and whether a label verified them
+    seeds: HashMap<T, Vec<(Vec<u64>, bool)>>,
+    // edge weights changed after construction, None if the edge was removed
+    edge_overrides: HashMap<EdgeIndex, Option<WeightsTuple>>,
 }
 
 pub type Bags<T> = HashMap<T, Bag<T>>;
@@ -51,6 +86,13 @@ pub enum MLCError {
     NodeMapNotSet,
     UnknownNodeId(usize),
     EmptyStartingQueue,
+    InvalidLimitCriterion(Criterion),
+    InvalidSeed(String),
+    UnknownEdgeId(usize),
+    InvalidEdgeUpdate(String),
+    InvalidCriterion(Criterion),
+    InvalidPreference(String),
+    InvalidTurnCost(String),
 }
 
 impl fmt::Display for MLCError {
@@ -65,55 +107,93 @@ impl fmt::Display for MLCError {
                 f,
                 "Starting queue is empty. Specify either a start node or a starting queue."
             ),
+            MLCError::InvalidLimitCriterion(criterion) => {
+                write!(f, "Invalid limit criterion: {}", criterion)
+            }
+            MLCError::InvalidSeed(reason) => write!(f, "Invalid seed: {}", reason),
+            MLCError::UnknownEdgeId(edge_id) => write!(f, "Unknown edge id: {}", edge_id),
+            MLCError::InvalidEdgeUpdate(reason) => write!(f, "Invalid edge update: {}", reason),
+            MLCError::InvalidCriterion(criterion) => write!(f, "Invalid criterion: {}", criterion),
+            MLCError::InvalidPreference(reason) => write!(f, "Invalid preference: {}", reason),
+            MLCError::InvalidTurnCost(reason) => write!(f, "Invalid turn cost: {}", reason),
         }
     }
 }
 
 impl Error for MLCError {}
 
-impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> MLC<'_, T> {
-    pub fn new(g: &Graph<Vec<T>, WeightsTuple, Directed>) -> Result<MLC<T>, Box<dyn Error>> {
+impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy, G: SearchGraph<T>> MLC<'_, T, G> {
+    pub fn new(g: &G) -> Result<MLC<'_, T, G>, Box<dyn Error>> {
         if g.edge_count() == 0 {
             return Err("Graph has no edges".into());
         }
 
-        let sample_edge_weight = g.edge_references().next().unwrap().weight();
+        let sample_edge_weight = (0..g.node_count())
+            .find_map(|node| g.out_edges(node).next())
+            .unwrap()
+            .weights;
         let n_weights = sample_edge_weight.weights.len();
         let n_hidden_weights = sample_edge_weight.hidden_weights.len();
 
-        for node in g.node_indices() {
-            for edge in g.edges(node) {
-                if n_weights != edge.weight().weights.len() {
-                    return Err("Graph has inconsistent edge weights".into());
-                }
-                if n_hidden_weights != edge.weight().hidden_weights.len() {
-                    return Err("Graph has inconsistent hidden edge weights".into());
+        for node in 0..g.node_count() {
+            for edge in g.out_edges(node) {
+                let inconsistent = if n_weights != edge.weights.weights.len() {
+                    Some("edge weights")
+                } else if n_hidden_weights != edge.weights.hidden_weights.len() {
+                    Some("hidden edge weights")
+                } else if ![0, n_weights].contains(&edge.weights.transfer_weights.len()) {
+                    Some("transfer weights")
+                } else if ![0, n_weights].contains(&edge.weights.wait_weights.len()) {
+                    Some("wait weights")
+                } else {
+                    None
+                };
+                if let Some(inconsistent) = inconsistent {
+                    return Err(format!(
+                        "Graph has inconsistent {}: edge {} from {} to {} (see validate::validate_graph)",
+                        inconsistent,
+                        edge.id.index(),
+                        node,
+                        edge.target
+                    )
+                    .into());
                 }
             }
         }
 
-        let mut limits = Limits::new();
-        let categories = g
-            .node_indices()
-            .map(|node| g.node_weight(node).unwrap())
-            .flatten()
+        let mut limits = Limits::new(n_weights);
+        let categories = (0..g.node_count())
+            .flat_map(|node| g.categories(node).unwrap())
             .collect::<HashSet<_>>();
         for category in categories {
-            limits.add_category(category.clone());
+            limits.add_category(*category);
         }
 
         Ok(MLC {
             graph: g,
             bags: HashMap::new(),
+            fronts: None,
             queue: BinaryHeap::new(),
             weight_length: n_weights,
             node_map: None,
             disable_paths: false,
             hidden_weights_length: n_hidden_weights,
             update_label_func: None,
-            debug: false,
+            progress_observer: None,
+            progress_interval: 1000,
             limits,
             enable_limit: false,
+            enable_timings: false,
+            limit_criteria: (0..n_weights).map(Criterion::Value).collect(),
+            edge_filter: None,
+            node_filter: None,
+            required_attributes: 0,
+            forbidden_attributes: 0,
+            automaton: None,
+            turn_costs: TurnCosts::new(),
+            statistics: Statistics::default(),
+            seeds: HashMap::new(),
+            edge_overrides: HashMap::new(),
         })
     }
 
@@ -124,8 +204,15 @@ impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> MLC<'_, T> {
         self.update_label_func = Some(update_label_func);
     }
 
-    pub fn set_debug(&mut self, debug: bool) {
-        self.debug = debug;
+    /// Sets an observer that is notified about the progress of `run`.
+    pub fn set_progress_observer(&mut self, observer: Box<dyn ProgressObserver>) {
+        self.progress_observer = Some(observer);
+    }
+
+    /// Sets after how many processed labels the progress observer is notified.
+    pub fn set_progress_interval(&mut self, progress_interval: usize) {
+        assert!(progress_interval > 0);
+        self.progress_interval = progress_interval;
     }
 
     pub fn set_node_map(&mut self, node_map: BiMap<String, usize>) {
@@ -140,6 +227,205 @@ impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> MLC<'_, T> {
         self.enable_limit = enable_limit;
     }
 
+    /// Measures the time spent in each phase of `run`, see `statistics::Timings`. This is off by
+    /// default, as it reads the clock several times per label.
+    pub fn set_enable_timings(&mut self, enable_timings: bool) {
+        self.enable_timings = enable_timings;
+    }
+
+    /// Sets the criteria that are used for the category limits. A label is discarded if, for
+    /// every category, a label that reached the category is at most as large in each of the
+    /// criteria.
+    ///
+    /// By default all values are used. Must be called before `set_bags` and `seed_limits`, as
+    /// seeded limits are values of the previous criteria; returns `MLCError::InvalidSeed` if
+    /// limits were seeded already.
+    pub fn set_limit_criteria(&mut self, criteria: Vec<Criterion>) -> Result<(), MLCError> {
+        if !self.seeds.is_empty() {
+            return Err(MLCError::InvalidSeed(
+                "limits were seeded before setting the limit criteria".to_string(),
+            ));
+        }
+        for criterion in criteria.iter() {
+            if !criterion.is_valid(self.weight_length, self.hidden_weights_length) {
+                return Err(MLCError::InvalidLimitCriterion(*criterion));
+            }
+        }
+        let mut limits = Limits::new(criteria.len());
+        for category in self.limits.categories() {
+            limits.add_category(*category);
+        }
+        limits.set_requirement(self.limits.requirement().clone());
+        self.limits = limits;
+        self.limit_criteria = criteria;
+        Ok(())
+    }
+
+    /// Sets a predicate that decides per edge whether it may be traversed.
+    pub fn set_edge_filter(&mut self, edge_filter: EdgeFilter) {
+        self.edge_filter = Some(edge_filter);
+    }
+
+    /// Sets a predicate that decides per node whether it may be entered. The start node is
+    /// always allowed.
+    pub fn set_node_filter(&mut self, node_filter: NodeFilter) {
+        self.node_filter = Some(node_filter);
+    }
+
+    /// Only traverses edges whose attributes contain all bits of `required` and none of the
+    /// bits of `forbidden`.
+    pub fn set_edge_attribute_filter(&mut self, required: u64, forbidden: u64) {
+        self.required_attributes = required;
+        self.forbidden_attributes = forbidden;
+    }
+
+    /// Constrains the paths to the regular language of the automaton over edge modes. The
+    /// search then runs on pairs of node and automaton state: labels keep their state in
+    /// `Label::state` and only dominate labels of the same state. Only labels in accepting
+    /// states update the category limits, see `accepted_bags` for the result.
+    ///
+    /// Must be called before `set_bags`.
+    pub fn set_automaton(&mut self, automaton: Automaton) {
+        self.automaton = Some(automaton);
+    }
+
+    /// Sets costs of pairs of consecutive edges, e.g. turns, see `turns::TurnCosts`. Labels then
+    /// remember the edge they arrived by if a turn starts at it, and only dominate labels of the
+    /// same edge.
+    ///
+    /// Returns `MLCError::InvalidTurnCost` if an edge does not exist, the edges are not
+    /// consecutive or the number of weights differs from the edge weights.
+    pub fn set_turn_costs(&mut self, turn_costs: TurnCosts) -> Result<(), MLCError> {
+        for (from, to, weights) in turn_costs.iter() {
+            let (_, via) = self
+                .graph
+                .edge_endpoints(from)
+                .ok_or(MLCError::UnknownEdgeId(from.index()))?;
+            let (u, _) = self
+                .graph
+                .edge_endpoints(to)
+                .ok_or(MLCError::UnknownEdgeId(to.index()))?;
+            if via != u {
+                return Err(MLCError::InvalidTurnCost(format!(
+                    "edge {} does not start where edge {} ends",
+                    to.index(),
+                    from.index()
+                )));
+            }
+            if weights.len() != self.weight_length {
+                return Err(MLCError::InvalidTurnCost(format!(
+                    "expected {} weights, got {}",
+                    self.weight_length,
+                    weights.len()
+                )));
+            }
+        }
+        self.turn_costs = turn_costs;
+        Ok(())
+    }
+
+    /// Sets how many categories must be covered before a label is discarded, e.g. to stop once
+    /// any `k` categories are reachable instead of all of them. Defaults to
+    /// `CoverageRequirement::All`.
+    pub fn set_limit_requirement(&mut self, requirement: CoverageRequirement<T>) {
+        self.limits.set_requirement(requirement);
+    }
+
+    /// Seeds the category limits, e.g. with limits from a file (see `limit::read_limits`), so
+    /// that a run prunes from the start.
+    ///
+    /// Seeds must be safe upper bounds: for each seeded limit, some path from the start must
+    /// reach the category with at most the seeded values. Otherwise labels are discarded that
+    /// would have been part of the result. This is not checked here, only the categories and
+    /// the number of values are. A seed is verified during the run once a label reaches the
+    /// category with at most the seeded values; check `unverified_seeds` after the run to find
+    /// seeds that may have pruned too much. A seed that is exactly tight can be reported as
+    /// well: the labels that would reach the category with the seeded values are pruned before
+    /// they reach it if their values are already equal to the seed, e.g. before a last edge
+    /// with zero weights.
+    ///
+    /// Must be called after `set_limit_criteria`.
+    pub fn seed_limits(&mut self, seeds: Seeds<T>) -> Result<(), MLCError> {
+        for (category, limits) in seeds.iter() {
+            if !self.limits.categories().any(|c| c == category) {
+                return Err(MLCError::InvalidSeed(
+                    "category does not exist in the graph".to_string(),
+                ));
+            }
+            if let Some(values) = limits
+                .iter()
+                .find(|values| values.len() != self.limits.dimension())
+            {
+                return Err(MLCError::InvalidSeed(format!(
+                    "expected {} values, got {}",
+                    self.limits.dimension(),
+                    values.len()
+                )));
+            }
+        }
+        for (category, limits) in seeds {
+            for values in limits {
+                self.limits.update_limit(category, &values);
+                self.seeds
+                    .entry(category)
+                    .or_default()
+                    .push((values, false));
+            }
+        }
+        Ok(())
+    }
+
+    /// Seeds the category limits with the limits of another run, shifted by `shift`.
+    ///
+    /// If the other run started at a node that can be reached from this run's start node with
+    /// at most `shift` in each limit criterion, every shifted limit is a safe upper bound.
+    pub fn seed_limits_from(
+        &mut self,
+        frontiers: &HashMap<T, Vec<Limit>>,
+        shift: &[u64],
+    ) -> Result<(), MLCError> {
+        if shift.len() != self.limits.dimension() {
+            return Err(MLCError::InvalidSeed(format!(
+                "expected a shift of {} values, got {}",
+                self.limits.dimension(),
+                shift.len()
+            )));
+        }
+        let seeds = frontiers
+            .iter()
+            .map(|(category, limits)| {
+                let limits = limits
+                    .iter()
+                    .map(|limit| {
+                        limit
+                            .values
+                            .iter()
+                            .zip(shift.iter())
+                            .map(|(value, shift)| value.saturating_add(*shift))
+                            .collect()
+                    })
+                    .collect();
+                (*category, limits)
+            })
+            .collect();
+        self.seed_limits(seeds)
+    }
+
+    /// Returns the seeded limits that no label of the run reached, i.e. that could not be
+    /// verified to be safe upper bounds. These are either unsafe or exactly tight, see
+    /// `seed_limits`.
+    pub fn unverified_seeds(&self) -> Vec<(T, Vec<u64>)> {
+        self.seeds
+            .iter()
+            .flat_map(|(category, seeds)| {
+                seeds
+                    .iter()
+                    .filter(|(_, verified)| !verified)
+                    .map(|(values, _)| (*category, values.clone()))
+            })
+            .collect()
+    }
+
     /// Sets the starting bags and derives the starting queue from them.
     ///
     /// The bags should be in a consistent state, meaning that the labels in the bags should
@@ -154,11 +440,8 @@ impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> MLC<'_, T> {
         let mut label_node_tuples = vec![];
         for bag in self.bags.values() {
             for label in &bag.labels {
-                let node_weight = self
-                    .graph
-                    .node_weight(NodeIndex::new(label.node_id))
-                    .unwrap();
-                if self.enable_limit && node_weight.len() > 0 {
+                let node_weight = self.graph.categories(label.node_id).unwrap();
+                if self.enable_limit && !node_weight.is_empty() && self.is_accepting(label) {
                     label_node_tuples.push((label.clone(), node_weight));
                 }
 
@@ -192,6 +475,9 @@ impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> MLC<'_, T> {
             hidden_values,
             path: start_path,
             node_id: start_node,
+            state: 0,
+            line: None,
+            edge: None,
         };
         self.queue.push(start_label.clone());
         self.bags
@@ -214,6 +500,9 @@ impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> MLC<'_, T> {
             hidden_values,
             path: start_path,
             node_id: start_node,
+            state: 0,
+            line: None,
+            edge: None,
         };
         self.queue.push(start_label.clone());
         self.bags
@@ -238,114 +527,244 @@ impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> MLC<'_, T> {
     /// * `start` - The node to start the algorithm at.
     ///
     /// # Returns
-    /// * `Bags<usize>` - The bags of each node.
+    /// * `Bags<usize>` - The bags of each node, reduced to their Pareto front per automaton
+    ///   state, see `Bag::front`. Statistics about the run are only available afterwards via
+    ///   `statistics`.
     pub fn run(&mut self) -> Result<&Bags<usize>, MLCError> {
         debug!("mlc config: {:?}", self);
 
         let mut counter = 0;
-        let mut time = Instant::now();
+        let start = Instant::now();
 
-        let mut n_limit_exceeded = 0;
+        let mut stats = Statistics::default();
+        let mut total_labels = self.bags.values().map(|bag| bag.labels.len()).sum();
+        stats.peak_queue_length = self.queue.len();
+        stats.peak_total_labels = total_labels;
 
         if self.enable_limit && !self.limits.is_initialized() {
             panic!("Limits must be initialized before running the algorithm");
         }
 
         while let Some(label) = self.queue.pop() {
-            if self.enable_limit && self.exceeds_limit(&label) {
-                n_limit_exceeded += 1;
-                continue;
+            stats.labels_popped += 1;
+
+            if self.enable_limit {
+                let limit_check_start = self.enable_timings.then(Instant::now);
+                let exceeds_limit = self.exceeds_limit(&label);
+                if let Some(limit_check_start) = limit_check_start {
+                    stats.timings.limit_check += limit_check_start.elapsed();
+                }
+                if exceeds_limit {
+                    stats.limit_discards += 1;
+                    continue;
+                }
             }
 
             let node_id = label.node_id;
 
             // check if this label is still in the bag of its node, if not, we can skip it
             // to speed up the algorithm (~20%)
-            if !self
+            let stale_check_start = self.enable_timings.then(Instant::now);
+            let is_stale = !self
                 .bags
                 .get(&node_id)
                 .ok_or(MLCError::UnknownNodeId(node_id))?
                 .labels
-                .contains(&label)
-            {
+                .contains(&label);
+            if let Some(stale_check_start) = stale_check_start {
+                stats.timings.stale_check += stale_check_start.elapsed();
+            }
+            if is_stale {
+                stats.labels_skipped_stale += 1;
                 continue;
             }
 
-            for edge in self.graph.edges(NodeIndex::new(node_id)) {
+            let expansion_start = self.enable_timings.then(Instant::now);
+            for edge in self.graph.out_edges(node_id) {
+                let weight = if self.edge_overrides.is_empty() {
+                    edge.weights
+                } else {
+                    match self.edge_overrides.get(&edge.id) {
+                        Some(Some(weight)) => weight.as_edge_weights(),
+                        // the edge was removed
+                        Some(None) => continue,
+                        None => edge.weights,
+                    }
+                };
+                if !self.is_traversable(edge.id, &weight, edge.target) {
+                    continue;
+                }
+                let state = match &self.automaton {
+                    Some(automaton) => match automaton.next(label.state, weight.mode) {
+                        Some(state) => state,
+                        None => continue,
+                    },
+                    None => label.state,
+                };
                 let old_label = label.clone();
-                let mut new_label = label.new_along(&edge, self.disable_paths);
+                let mut new_label =
+                    label.new_along_weights(weight, edge.target, self.disable_paths);
+                new_label.state = state;
+                if !self.turn_costs.is_empty() {
+                    let turn_weights = label.edge.and_then(|previous| {
+                        self.turn_costs.get(EdgeIndex::new(previous), edge.id)
+                    });
+                    if let Some(turn_weights) = turn_weights {
+                        new_label.add_turn_weights(turn_weights);
+                    }
+                    if self.turn_costs.starts_at(edge.id) {
+                        new_label.edge = Some(edge.id.index());
+                    }
+                }
                 if let Some(update_label_func) = self.update_label_func {
                     new_label = update_label_func(&old_label, &new_label);
                 }
-                let target_bag = self
-                    .bags
-                    .entry(edge.target().index())
-                    .or_insert_with(Bag::new_empty);
-                if target_bag.add_if_necessary(new_label.clone()) {
-                    let target_node_values = self
-                        .graph
-                        .node_weight(edge.target())
-                        .ok_or(MLCError::UnknownNodeId(edge.target().index()))?;
-                    if self.enable_limit && target_node_values.len() > 0 {
-                        self.update_limits(&new_label, target_node_values);
+                stats.labels_created += 1;
+                let target_bag = self.bags.entry(edge.target).or_insert_with(Bag::new_empty);
+                match target_bag.add_if_necessary_counting_removed(new_label.clone()) {
+                    Some(n_removed) => {
+                        stats.labels_removed_from_bags += n_removed;
+                        total_labels = total_labels + 1 - n_removed;
+                        let target_node_values = self
+                            .graph
+                            .categories(edge.target)
+                            .ok_or(MLCError::UnknownNodeId(edge.target))?;
+                        if self.enable_limit
+                            && !target_node_values.is_empty()
+                            && self.is_accepting(&new_label)
+                        {
+                            self.update_limits(&new_label, target_node_values);
+                        }
+                        self.queue.push(new_label);
                     }
-                    self.queue.push(new_label);
+                    None => stats.labels_rejected_dominance += 1,
                 }
             }
+            if let Some(expansion_start) = expansion_start {
+                stats.timings.expansion += expansion_start.elapsed();
+            }
+            stats.peak_queue_length = stats.peak_queue_length.max(self.queue.len());
+            stats.peak_total_labels = stats.peak_total_labels.max(total_labels);
 
             counter += 1;
-            // print queue size every 1000 iterations
-            // if debug is enabled, write labels to csv every 10 seconds
-            if counter % 1000 == 0 {
+            if counter % self.progress_interval == 0 {
                 debug!("queue size: {}", self.queue.len());
-                if self.debug {
-                    let duration = time.elapsed();
-                    if duration.as_secs() > 10 {
-                        info!("writing labels to csv");
-                        write_bags(&self.translate_bags(&self.bags), "data/labels.csv").unwrap();
-                        time = Instant::now();
-                    }
+                if let Some(observer) = self.progress_observer.as_mut() {
+                    let progress = Self::progress(counter, self.queue.len(), total_labels, start);
+                    observer.on_progress(&progress, &self.bags);
                 }
             }
         }
 
-        if self.enable_limit && n_limit_exceeded > 0 {
+        if let Some(observer) = self.progress_observer.as_mut() {
+            let progress = Self::progress(counter, self.queue.len(), total_labels, start);
+            observer.on_finish(&progress, &self.bags);
+        }
+
+        if self.enable_limit && stats.limit_discards > 0 {
             debug!(
                 "{} labels were discarded because they exceeded the limit",
-                n_limit_exceeded
+                stats.limit_discards
             );
         }
 
-        Ok(&self.bags)
+        let n_unverified_seeds = self.unverified_seeds().len();
+        if self.enable_limit && n_unverified_seeds > 0 {
+            warn!(
+                "{} seeded limits were not reached by any label and may be unsafe, or exactly \
+                 tight so that they pruned the labels that would have reached them",
+                n_unverified_seeds
+            );
+        }
+
+        // labels of different lines or edges do not dominate each other during the search
+        self.fronts = self
+            .bags
+            .values()
+            .any(|bag| bag.has_several_contexts())
+            .then(|| {
+                self.bags
+                    .iter()
+                    .map(|(node_id, bag)| (*node_id, bag.front()))
+                    .collect()
+            });
+
+        stats.timings.total = start.elapsed();
+        debug!("mlc statistics: {:?}", stats);
+        self.statistics = stats;
+
+        Ok(self.fronts.as_ref().unwrap_or(&self.bags))
     }
 
-    fn translate_bags(&self, bags: &Bags<usize>) -> Bags<String> {
-        let node_map = self
-            .node_map
-            .as_ref()
-            .expect("node_map must be passed when calling translate_bags");
-        let mut translated_bags: Bags<String> = HashMap::new();
-        for (node_id, bag) in bags {
-            let translated_node_id = node_map.get_by_right(node_id).unwrap();
-            let translated_bag = Bag {
+    /// Returns the category limits. After a run with limits enabled, they contain for each
+    /// category the Pareto front of the limit criteria at which it was reached, together with
+    /// the labels that reached it.
+    pub fn limits(&self) -> &Limits<T> {
+        &self.limits
+    }
+
+    /// Returns the categories whose limits cover the label in the limit criteria, and whether
+    /// they satisfy the coverage requirement, i.e. whether the label would be discarded.
+    pub fn limit_coverage(&self, label: &Label<usize>) -> Coverage<T> {
+        self.limits.coverage(&self.limit_values(label))
+    }
+
+    /// Returns the labels of the last run whose path is accepted by the automaton, with dominance
+    /// applied across automaton states, lines and edges. Of equal labels, the one with the lowest
+    /// state is kept. Nodes without accepted labels are omitted. Without an automaton, the bags
+    /// returned by `run` are returned.
+    pub fn accepted_bags(&self) -> Bags<usize> {
+        if self.automaton.is_none() {
+            return self.fronts.as_ref().unwrap_or(&self.bags).clone();
+        }
+        let mut accepted_bags = HashMap::new();
+        for (node_id, bag) in self.bags.iter() {
+            let accepted = Bag {
                 labels: bag
                     .labels
                     .iter()
-                    .map(|label| Label {
-                        node_id: translated_node_id.clone(),
-                        path: label
-                            .path
-                            .iter()
-                            .map(|n| node_map.get_by_right(n).unwrap().to_string())
-                            .collect(),
-                        values: label.values.clone(),
-                        hidden_values: label.hidden_values.clone(),
-                    })
+                    .filter(|label| self.is_accepting(label))
+                    .cloned()
                     .collect(),
             };
-            translated_bags.insert(translated_node_id.to_string(), translated_bag);
+            if !accepted.labels.is_empty() {
+                accepted_bags.insert(*node_id, accepted.front_across_states());
+            }
+        }
+        accepted_bags
+    }
+
+    /// Returns the `accepted_bags` of the last run for queries, with paths translated via the
+    /// node map if one was set.
+    pub fn results(&self) -> Results<'_> {
+        Results::new(self.accepted_bags(), self.node_map.as_ref())
+    }
+
+    /// Returns the statistics of the last call to `run`. This is the only way to get them, `run`
+    /// returns the bags only.
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    fn progress(
+        labels_processed: usize,
+        queue_size: usize,
+        total_bag_size: usize,
+        start: Instant,
+    ) -> Progress {
+        Progress {
+            labels_processed,
+            queue_size,
+            total_bag_size,
+            elapsed: start.elapsed(),
         }
-        translated_bags
+    }
+
+    /// Translates bags, e.g. of the last run, to the external node ids of the node map, see
+    /// `translate_bags`.
+    pub fn translate_bags(&self, bags: &Bags<usize>) -> Result<Bags<String>, MLCError> {
+        let node_map = self.node_map.as_ref().ok_or(MLCError::NodeMapNotSet)?;
+        translate_bags(bags, node_map)
     }
 
     #[allow(dead_code)]
@@ -362,33 +781,70 @@ impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> MLC<'_, T> {
         }
     }
 
-    fn exceeds_limit(&mut self, label: &Label<usize>) -> bool {
-        let values = &label.values;
-        if values.len() != 2 {
-            return false;
-        }
-        let cost = values[1];
-        let time = values[0];
-        let result = self.limits.is_limit_exceeded(cost, time);
-        return result;
-    }
-
-    fn update_limits(&mut self, label: &Label<usize>, node_values: &Vec<T>) {
-        for value in node_values.iter() {
-            let category = value;
-            let cost = label.values[1];
-            let time = label.values[0];
-            self.limits.update_limit(*category, cost, time);
+    fn is_traversable(&self, edge_id: EdgeIndex, weight: &EdgeWeights, target: usize) -> bool {
+        weight.attributes & self.required_attributes == self.required_attributes
+            && weight.attributes & self.forbidden_attributes == 0
+            && self
+                .edge_filter
+                .as_ref()
+                .is_none_or(|edge_filter| edge_filter(edge_id, weight))
+            && self
+                .node_filter
+                .as_ref()
+                .is_none_or(|node_filter| node_filter(target))
+    }
+
+    fn is_accepting(&self, label: &Label<usize>) -> bool {
+        self.automaton
+            .as_ref()
+            .is_none_or(|automaton| automaton.is_accepting(label.state))
+    }
+
+    fn exceeds_limit(&self, label: &Label<usize>) -> bool {
+        self.limits.is_limit_exceeded(&self.limit_values(label))
+    }
+
+    fn update_limits(&mut self, label: &Label<usize>, node_values: &[T]) {
+        let values = self.limit_values(label);
+        for category in node_values.iter() {
+            self.limits
+                .update_limit_with_label(*category, &values, label);
+            if let Some(seeds) = self.seeds.get_mut(category) {
+                for (seed, verified) in seeds.iter_mut() {
+                    *verified |= values.iter().zip(seed.iter()).all(|(v, s)| v <= s);
+                }
+            }
         }
     }
+
+    fn limit_values(&self, label: &Label<usize>) -> Vec<u64> {
+        self.limit_criteria
+            .iter()
+            .map(|criterion| label.criterion(*criterion))
+            .collect()
+    }
 }
 
-impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> fmt::Debug for MLC<'_, T> {
+impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy, G: SearchGraph<T>> fmt::Debug
+    for MLC<'_, T, G>
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("MLC")
-            .field("debug", &self.debug)
+            .field(
+                "progress_observer_defined",
+                &self.progress_observer.is_some(),
+            )
+            .field("progress_interval", &self.progress_interval)
             .field("disable_paths", &self.disable_paths)
             .field("enable_limit", &self.enable_limit)
+            .field("enable_timings", &self.enable_timings)
+            .field("limit_criteria", &self.limit_criteria)
+            .field("edge_filter_defined", &self.edge_filter.is_some())
+            .field("node_filter_defined", &self.node_filter.is_some())
+            .field("required_attributes", &self.required_attributes)
+            .field("forbidden_attributes", &self.forbidden_attributes)
+            .field("automaton", &self.automaton)
+            .field("turn_costs", &self.turn_costs.iter().count())
             .field(
                 "update_label_func_defined",
                 &self.update_label_func.is_some(),
@@ -400,88 +856,68 @@ impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> fmt::Debug for MLC<'
 }
 // impl<T> fmt::Debug for MLC<T> {}
 
-#[derive(Debug)]
-struct LabelEntry {
-    node_id: NodeId,
-    path: Vec<NodeId>,
-    values: Vec<Weight>,
+/// Translates the node ids and paths of the bags to the external node ids of the node map, e.g.
+/// as returned by `read_graph_and_reset_ids`. All other fields of the labels are kept.
+///
+/// The translated bags can be written by `write_bags` and the functions of `geojson`, which
+/// accept any node ids. Returns `MLCError::UnknownNodeId` if a node is not in the node map.
+pub fn translate_bags(
+    bags: &Bags<usize>,
+    node_map: &BiMap<String, usize>,
+) -> Result<Bags<String>, MLCError> {
+    let mut translated_bags: Bags<String> = HashMap::new();
+    for (node_id, bag) in bags {
+        translated_bags.insert(
+            translate_node(*node_id, node_map)?,
+            translate_bag(bag, node_map)?,
+        );
+    }
+    Ok(translated_bags)
 }
 
-impl FromStr for LabelEntry {
-    type Err = ParseIntError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // node_id|path_node1,path_node2,...|value1,value2,...
-        let mut parts = s.split('|');
-
-        let node_id = parts.next().unwrap().parse::<NodeId>()?;
-        let path = parts
-            .next()
-            .unwrap()
-            .split(',')
-            .map(|s| s.parse::<NodeId>())
-            .collect::<Result<Vec<NodeId>, _>>()?;
-        let values = parts
-            .next()
-            .unwrap()
-            .split(',')
-            .map(|s| s.parse::<Weight>())
-            .collect::<Result<Vec<Weight>, _>>()?;
-        Ok(LabelEntry {
-            node_id,
-            path,
-            values,
+/// Like `translate_bags`, for a single bag.
+pub fn translate_bag(
+    bag: &Bag<usize>,
+    node_map: &BiMap<String, usize>,
+) -> Result<Bag<String>, MLCError> {
+    let labels = bag
+        .labels
+        .iter()
+        .map(|label| {
+            Ok(Label {
+                node_id: translate_node(label.node_id, node_map)?,
+                state: label.state,
+                line: label.line,
+                edge: label.edge,
+                path: label
+                    .path
+                    .iter()
+                    .map(|node| translate_node(*node, node_map))
+                    .collect::<Result<_, _>>()?,
+                values: label.values.clone(),
+                hidden_values: label.hidden_values.clone(),
+            })
         })
-    }
+        .collect::<Result<_, MLCError>>()?;
+    Ok(Bag { labels })
 }
 
-pub fn read_bags(path: &str) -> Result<Bags<usize>, Box<dyn Error>> {
-    let mut bags: Bags<usize> = HashMap::new();
-    for line in read_to_string(path)?.lines().skip(1) {
-        let label_entry: LabelEntry = line.parse()?;
-        let label = Label {
-            values: label_entry.values.clone(),
-            hidden_values: vec![],
-            path: label_entry.path.clone(),
-            node_id: label_entry.node_id,
-        };
-        let bag = bags
-            .entry(label_entry.node_id)
-            .or_insert_with(Bag::new_empty);
-        bag.add_if_necessary(label);
-    }
-    Ok(bags)
+pub(crate) fn translate_node(
+    node_id: usize,
+    node_map: &BiMap<String, usize>,
+) -> Result<String, MLCError> {
+    node_map
+        .get_by_right(&node_id)
+        .cloned()
+        .ok_or(MLCError::UnknownNodeId(node_id))
 }
 
-pub fn write_bags<T: Eq + Hash + Display>(
-    bags: &Bags<T>,
+/// Like `write_bags`, but writes the node ids and paths as the external node ids of the node
+/// map, see `translate_bags`.
+pub fn write_translated_bags(
+    bags: &Bags<usize>,
+    node_map: &BiMap<String, usize>,
     path: &str,
 ) -> Result<(), Box<dyn Error>> {
-    let mut file = std::fs::File::create(path)?;
-    let header = "node_id|path|weights\n";
-    file.write_all(header.as_bytes())?;
-
-    for bag in bags.values() {
-        for label in bag.labels.iter() {
-            let mut values = label.values.clone();
-            values.extend(label.hidden_values.clone());
-            let line = format!(
-                "{}|{}|{}\n",
-                label.node_id,
-                label
-                    .path
-                    .iter()
-                    .map(|n| n.to_string())
-                    .collect::<Vec<String>>()
-                    .join(","),
-                values
-                    .iter()
-                    .map(|n| n.to_string())
-                    .collect::<Vec<String>>()
-                    .join(",")
-            );
-
-            file.write_all(line.as_bytes())?;
-        }
-    }
-    Ok(())
+    write_bags(&translate_bags(bags, node_map)?, path)
 }
@@ -0,0 +1,50 @@
+mod test;
+
+use std::collections::HashMap;
+
+/// A deterministic finite automaton over edge modes, used to constrain the paths of a query to
+/// a regular language, e.g. "walk, then transit, then walk" (see `MLC::set_automaton`).
+///
+/// State 0 is the start state. An edge can only be traversed if the automaton has a transition
+/// for its mode from the current state, and a path is accepted if it ends in an accepting state.
+#[derive(Debug, Clone)]
+pub struct Automaton {
+    transitions: Vec<HashMap<u32, usize>>,
+    accepting: Vec<bool>,
+}
+
+impl Automaton {
+    /// Creates an automaton with `n_states` states, no transitions and no accepting states.
+    pub fn new(n_states: usize) -> Automaton {
+        assert!(n_states > 0);
+        Automaton {
+            transitions: vec![HashMap::new(); n_states],
+            accepting: vec![false; n_states],
+        }
+    }
+
+    pub fn n_states(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// Adds a transition from `from` to `to` along edges with the given mode, replacing an
+    /// existing transition for the same state and mode.
+    pub fn add_transition(&mut self, from: usize, mode: u32, to: usize) {
+        assert!(from < self.n_states() && to < self.n_states());
+        self.transitions[from].insert(mode, to);
+    }
+
+    pub fn set_accepting(&mut self, state: usize) {
+        self.accepting[state] = true;
+    }
+
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.accepting[state]
+    }
+
+    /// Returns the state after traversing an edge with the given mode, or `None` if the edge
+    /// may not be traversed from `state`.
+    pub fn next(&self, state: usize, mode: u32) -> Option<usize> {
+        self.transitions[state].get(&mode).copied()
+    }
+}
@@ -0,0 +1,29 @@
+#[cfg(test)]
+mod tests {
+    use crate::mlc::automaton::Automaton;
+
+    #[test]
+    fn test_automaton() {
+        // walk (0), then transit (1), then walk
+        let mut automaton = Automaton::new(3);
+        automaton.add_transition(0, 0, 0);
+        automaton.add_transition(0, 1, 1);
+        automaton.add_transition(1, 1, 1);
+        automaton.add_transition(1, 0, 2);
+        automaton.add_transition(2, 0, 2);
+        automaton.set_accepting(0);
+        automaton.set_accepting(2);
+
+        let run = |modes: &[u32]| {
+            modes
+                .iter()
+                .try_fold(0, |state, mode| automaton.next(state, *mode))
+                .filter(|state| automaton.is_accepting(*state))
+        };
+        assert_eq!(run(&[0, 0]), Some(0));
+        assert_eq!(run(&[0, 1, 1, 0]), Some(2));
+        assert_eq!(run(&[0, 1]), None);
+        assert_eq!(run(&[1, 0, 1]), None);
+        assert_eq!(run(&[2]), None);
+    }
+}
@@ -0,0 +1,139 @@
+mod test;
+
+use std::collections::HashSet;
+
+use petgraph::graph::EdgeIndex;
+
+use super::{Bags, MLCError, MLC};
+use crate::bag::{EdgeWeights, WeightsTuple};
+use crate::graph::SearchGraph;
+
+/// A change of a single edge of the graph, applied with `MLC::update_edges`.
+#[derive(Debug, Clone)]
+pub enum EdgeUpdate {
+    /// Sets new weights for the edge. Each weight may increase or decrease.
+    SetWeights(EdgeIndex, WeightsTuple),
+    /// Removes the edge.
+    Remove(EdgeIndex),
+}
+
+impl EdgeUpdate {
+    fn edge(&self) -> EdgeIndex {
+        match self {
+            EdgeUpdate::SetWeights(edge, _) => *edge,
+            EdgeUpdate::Remove(edge) => *edge,
+        }
+    }
+}
+
+impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy, G: SearchGraph<T>> MLC<'_, T, G> {
+    /// Applies the edge updates after a call to `run` and repairs the bags, so that they are
+    /// equal to the bags of a new run on the updated graph.
+    ///
+    /// Labels whose path uses an edge whose weights increased, whose attributes or mode changed
+    /// or that was removed are invalidated. Then the labels of the updated edges' sources and of
+    /// the predecessors of every node that lost labels are propagated again. Paths must be
+    /// enabled to find the invalidated labels, and limits must be disabled, because the limits
+    /// of invalidated labels would no longer be safe.
+    ///
+    /// The graph itself is not modified; the updated weights are only used by this instance.
+    pub fn update_edges(&mut self, updates: Vec<EdgeUpdate>) -> Result<&Bags<usize>, MLCError> {
+        if self.disable_paths {
+            return Err(MLCError::InvalidEdgeUpdate(
+                "paths must be enabled".to_string(),
+            ));
+        }
+        if self.enable_limit {
+            return Err(MLCError::InvalidEdgeUpdate(
+                "limits must be disabled".to_string(),
+            ));
+        }
+
+        // all updates are checked first, so that an invalid update leaves the instance unchanged
+        let mut checked_updates = vec![];
+        for update in updates {
+            let edge = update.edge();
+            let (u, v) = self
+                .graph
+                .edge_endpoints(edge)
+                .ok_or(MLCError::UnknownEdgeId(edge.index()))?;
+            let new_weight = match update {
+                EdgeUpdate::SetWeights(_, weight) => {
+                    if weight.weights.len() != self.weight_length
+                        || weight.hidden_weights.len() != self.hidden_weights_length
+                        || ![0, self.weight_length].contains(&weight.transfer_weights.len())
+                        || ![0, self.weight_length].contains(&weight.wait_weights.len())
+                    {
+                        return Err(MLCError::InvalidEdgeUpdate(format!(
+                            "edge {} has inconsistent weights",
+                            edge.index()
+                        )));
+                    }
+                    Some(weight)
+                }
+                EdgeUpdate::Remove(_) => None,
+            };
+            checked_updates.push((edge, u, v, new_weight));
+        }
+
+        let mut invalidated_edges = vec![];
+        let mut sources = HashSet::new();
+        for (edge, u, v, new_weight) in checked_updates {
+            let invalidates = match (self.current_weight(edge), &new_weight) {
+                (Some(old), Some(new)) => {
+                    old.weights
+                        .iter()
+                        .zip(new.weights.iter())
+                        .any(|(o, n)| n > o)
+                        || old.hidden_weights != new.hidden_weights
+                        || old.transfer_weights != new.transfer_weights
+                        || old.wait_weights != new.wait_weights
+                        || old.line != new.line
+                        || old.attributes != new.attributes
+                        || old.mode != new.mode
+                }
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            if invalidates {
+                invalidated_edges.push((u, v));
+            }
+            sources.insert(u);
+            self.edge_overrides.insert(edge, new_weight);
+        }
+
+        let mut affected_nodes = vec![];
+        for (node_id, bag) in self.bags.iter_mut() {
+            let n_labels = bag.labels.len();
+            bag.labels.retain(|label| {
+                !invalidated_edges
+                    .iter()
+                    .any(|(u, v)| label.path_uses(*u, *v))
+            });
+            if bag.labels.len() != n_labels {
+                affected_nodes.push(*node_id);
+            }
+        }
+
+        // the labels removed from a bag might be replaced by labels of its predecessors
+        for node_id in affected_nodes {
+            sources.extend(self.graph.predecessors(node_id));
+        }
+        for node_id in sources {
+            if let Some(bag) = self.bags.get(&node_id) {
+                self.queue.extend(bag.labels.iter().cloned());
+            }
+        }
+
+        self.run()
+    }
+
+    /// Returns the weights of the edge, taking updates into account, or `None` if it was
+    /// removed.
+    fn current_weight(&self, edge: EdgeIndex) -> Option<EdgeWeights<'_>> {
+        match self.edge_overrides.get(&edge) {
+            Some(weight) => weight.as_ref().map(|weight| weight.as_edge_weights()),
+            None => self.graph.edge_weights(edge),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::{EdgeIndex, NodeIndex};
+
+    use super::super::*;
+    use crate::bag::{Bag, WeightsTuple};
+    use crate::mlc::automaton::Automaton;
+    use crate::read::MLCGraph;
+    use crate::test_util::Lcg;
+
+    fn weights(weights: Vec<u64>) -> WeightsTuple {
+        WeightsTuple {
+            weights,
+            hidden_weights: vec![],
+            ..Default::default()
+        }
+    }
+
+    fn build_graph(edges: &[Option<(usize, usize, Vec<u64>)>]) -> MLCGraph<()> {
+        let mut g = MLCGraph::<()>::with_capacity(0, edges.len());
+        let n_nodes = edges
+            .iter()
+            .flatten()
+            .map(|(u, v, _)| u.max(v) + 1)
+            .max()
+            .unwrap_or(0);
+        for _ in 0..n_nodes {
+            g.add_node(vec![]);
+        }
+        for (u, v, w) in edges.iter().flatten() {
+            g.add_edge(NodeIndex::new(*u), NodeIndex::new(*v), weights(w.clone()));
+        }
+        g
+    }
+
+    fn non_empty(bags: &Bags<usize>) -> Bags<usize> {
+        bags.iter()
+            .filter(|(_, bag)| !bag.labels.is_empty())
+            .map(|(node_id, bag)| (*node_id, bag.clone()))
+            .collect()
+    }
+
+    fn full_recompute(edges: &[Option<(usize, usize, Vec<u64>)>]) -> Bags<usize> {
+        let g = build_graph(edges);
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        non_empty(mlc.run().unwrap())
+    }
+
+    #[test]
+    fn test_update_edges_matches_full_recompute() {
+        let mut rng = Lcg(7);
+        let n_nodes = 12;
+        let mut edges = (0..40)
+            .map(|i| {
+                // make sure every node has an outgoing edge so the graph is mostly connected
+                let u = if i < n_nodes {
+                    i
+                } else {
+                    rng.next(n_nodes as u64) as usize
+                };
+                let v = rng.next(n_nodes as u64) as usize;
+                Some((u, v, vec![rng.next(10), rng.next(10)]))
+            })
+            .collect::<Vec<_>>();
+        let g = build_graph(&edges);
+
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        mlc.run().unwrap();
+
+        for _ in 0..30 {
+            let mut updates = vec![];
+            for _ in 0..1 + rng.next(3) {
+                let edge = rng.next(edges.len() as u64) as usize;
+                if rng.next(4) == 0 {
+                    edges[edge] = None;
+                    updates.push(EdgeUpdate::Remove(EdgeIndex::new(edge)));
+                } else {
+                    let (u, v) = g.edge_endpoints(EdgeIndex::new(edge)).unwrap();
+                    let w = vec![rng.next(10), rng.next(10)];
+                    edges[edge] = Some((u.index(), v.index(), w.clone()));
+                    updates.push(EdgeUpdate::SetWeights(EdgeIndex::new(edge), weights(w)));
+                }
+            }
+            let bags = non_empty(mlc.update_edges(updates).unwrap());
+            assert_eq!(bags, full_recompute(&edges));
+        }
+    }
+
+    #[test]
+    fn test_update_edges_removes_unreachable_labels() {
+        let edges = vec![Some((0, 1, vec![1, 1])), Some((1, 2, vec![1, 1]))];
+        let g = build_graph(&edges);
+
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        mlc.run().unwrap();
+
+        let bags = mlc
+            .update_edges(vec![EdgeUpdate::Remove(EdgeIndex::new(0))])
+            .unwrap();
+        assert_eq!(bags[&1], Bag::new_empty());
+        assert_eq!(bags[&2], Bag::new_empty());
+
+        let bags = mlc
+            .update_edges(vec![EdgeUpdate::SetWeights(
+                EdgeIndex::new(0),
+                weights(vec![2, 0]),
+            )])
+            .unwrap();
+        let label = bags[&2].labels.iter().next().unwrap();
+        assert_eq!(label.values, vec![3, 1]);
+        assert_eq!(label.path, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_update_edges_invalidates_forbidden_edges() {
+        let edges = vec![Some((0, 1, vec![1, 1])), Some((1, 2, vec![1, 1]))];
+        let g = build_graph(&edges);
+
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_edge_attribute_filter(0, 0b1);
+        mlc.set_start_node(0);
+        mlc.run().unwrap();
+
+        let forbidden = WeightsTuple {
+            attributes: 0b1,
+            ..weights(vec![1, 1])
+        };
+        let bags = mlc
+            .update_edges(vec![EdgeUpdate::SetWeights(EdgeIndex::new(1), forbidden)])
+            .unwrap();
+        assert_eq!(bags[&1].labels.len(), 1);
+        assert_eq!(bags[&2], Bag::new_empty());
+    }
+
+    #[test]
+    fn test_update_edges_invalidates_changed_modes() {
+        let edges = vec![Some((0, 1, vec![1, 1])), Some((1, 2, vec![1, 1]))];
+        let g = build_graph(&edges);
+
+        // only paths of mode 0 edges are allowed
+        let mut automaton = Automaton::new(1);
+        automaton.add_transition(0, 0, 0);
+        automaton.set_accepting(0);
+
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_automaton(automaton);
+        mlc.set_start_node(0);
+        mlc.run().unwrap();
+
+        let other_mode = WeightsTuple {
+            mode: 1,
+            ..weights(vec![1, 1])
+        };
+        let bags = mlc
+            .update_edges(vec![EdgeUpdate::SetWeights(EdgeIndex::new(1), other_mode)])
+            .unwrap();
+        assert_eq!(bags[&1].labels.len(), 1);
+        assert_eq!(bags[&2], Bag::new_empty());
+    }
+
+    #[test]
+    fn test_update_edges_rejects_invalid_updates() {
+        let g = build_graph(&[Some((0, 1, vec![1, 1]))]);
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        mlc.run().unwrap();
+
+        assert!(mlc
+            .update_edges(vec![EdgeUpdate::Remove(EdgeIndex::new(1))])
+            .is_err());
+        assert!(mlc
+            .update_edges(vec![EdgeUpdate::SetWeights(
+                EdgeIndex::new(0),
+                weights(vec![1])
+            )])
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_edges_keeps_state_on_invalid_update() {
+        let g = build_graph(&[Some((0, 1, vec![1, 1])), Some((1, 2, vec![1, 1]))]);
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap().clone();
+
+        // the first update is valid, the second one has too few weights
+        assert!(mlc
+            .update_edges(vec![
+                EdgeUpdate::Remove(EdgeIndex::new(0)),
+                EdgeUpdate::SetWeights(EdgeIndex::new(1), weights(vec![1])),
+            ])
+            .is_err());
+        assert!(mlc.edge_overrides.is_empty());
+        assert_eq!(mlc.bags, bags);
+    }
+}
@@ -0,0 +1,300 @@
+mod test;
+
+use std::error::Error;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::str::FromStr;
+
+use crate::bag::{Bag, Label, Weight};
+use crate::compression;
+
+use super::Bags;
+
+/// The delimiters of a labels file, see `write_bags`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LabelsFormat {
+    /// Separates the columns.
+    pub delimiter: u8,
+    /// Separates the node ids of the path within the path column.
+    pub list_delimiter: u8,
+}
+
+impl LabelsFormat {
+    /// Checks that the delimiters can be told apart from each other and from quotes and line
+    /// breaks, which are used to quote node ids.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.delimiter == self.list_delimiter {
+            return Err(format!(
+                "the delimiter and the list delimiter must differ, both are {:?}",
+                self.delimiter as char
+            ));
+        }
+        for delimiter in [self.delimiter, self.list_delimiter] {
+            if [b'"', b'\n', b'\r'].contains(&delimiter) {
+                return Err(format!("invalid delimiter {:?}", delimiter as char));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for LabelsFormat {
+    fn default() -> Self {
+        LabelsFormat {
+            delimiter: b'|',
+            list_delimiter: b',',
+        }
+    }
+}
+
+/// Writes the labels of the bags as csv with the default format, see `write_bags_with_format`.
+pub fn write_bags<T: Eq + Hash + Display>(
+    bags: &Bags<T>,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    write_bags_with_format(bags, path, &LabelsFormat::default())
+}
+
+/// Writes the labels of the bags as csv, one label per row, compressed if the path ends with
+/// `.gz` or `.zst`, see `compression::create`.
+///
+/// The header is `node_id|path|value_0|...|hidden_value_0|...`, so it declares the number of
+/// values and hidden values, which must be the same for all labels. The path is a list of node
+/// ids separated by the list delimiter. Node ids that contain a delimiter, a quote or a line
+/// break are quoted, so any string ids can be written.
+pub fn write_bags_with_format<T: Eq + Hash + Display>(
+    bags: &Bags<T>,
+    path: &str,
+    format: &LabelsFormat,
+) -> Result<(), Box<dyn Error>> {
+    format.validate()?;
+    let first = bags.values().find_map(|bag| bag.labels.iter().next());
+    let n_values = first.map_or(0, |label| label.values.len());
+    let n_hidden_values = first.map_or(0, |label| label.hidden_values.len());
+    // checked before the file is created, so that no partial file is left
+    for label in bags.values().flat_map(|bag| bag.labels.iter()) {
+        if label.values.len() != n_values || label.hidden_values.len() != n_hidden_values {
+            return Err(format!(
+                "Label of node {} has {} values and {} hidden values, expected {} and {}",
+                label.node_id,
+                label.values.len(),
+                label.hidden_values.len(),
+                n_values,
+                n_hidden_values
+            )
+            .into());
+        }
+    }
+
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(format.delimiter)
+        .from_writer(compression::create(path)?);
+    let mut header = vec!["node_id".to_string(), "path".to_string()];
+    header.extend((0..n_values).map(|i| format!("value_{}", i)));
+    header.extend((0..n_hidden_values).map(|i| format!("hidden_value_{}", i)));
+    wtr.write_record(&header)?;
+
+    for bag in bags.values() {
+        for label in bag.labels.iter() {
+            let mut record = vec![label.node_id.to_string(), join_path(label, format)];
+            record.extend(label.values.iter().map(|value| value.to_string()));
+            record.extend(label.hidden_values.iter().map(|value| value.to_string()));
+            wtr.write_record(&record)?;
+        }
+    }
+    wtr.into_inner().map_err(|err| err.into_error())?.finish()?;
+    Ok(())
+}
+
+// joins the node ids of the path, quoting them like csv fields if necessary
+fn join_path<T: Display>(label: &Label<T>, format: &LabelsFormat) -> String {
+    let delimiter = format.list_delimiter as char;
+    let nodes = label
+        .path
+        .iter()
+        .map(|node| {
+            let node = node.to_string();
+            if node.is_empty() && label.path.len() == 1
+                || node.contains([delimiter, '"', '\n', '\r'])
+            {
+                format!("\"{}\"", node.replace('"', "\"\""))
+            } else {
+                node
+            }
+        })
+        .collect::<Vec<_>>();
+    nodes.join(&delimiter.to_string())
+}
+
+/// Reads bags written by `write_bags`, which may be compressed with gzip or zstd, see
+/// `compression::open`. Dominated labels are dropped.
+pub fn read_bags(path: &str) -> Result<Bags<usize>, Box<dyn Error>> {
+    read_bags_with_format(path, &LabelsFormat::default())
+}
+
+/// Like `read_bags`, but for files written by `write_bags_with_format`.
+pub fn read_bags_with_format(
+    path: &str,
+    format: &LabelsFormat,
+) -> Result<Bags<usize>, Box<dyn Error>> {
+    let mut bags: Bags<usize> = Bags::new();
+    for label in read_labels(path, format)? {
+        bags.entry(label.node_id)
+            .or_insert_with(Bag::new_empty)
+            .add_if_necessary(label);
+    }
+    Ok(bags)
+}
+
+/// Reads the labels of a labels file, with node ids of any type, e.g. `String` for files
+/// written from translated bags.
+///
+/// Files of the older format with the header `node_id|path|weights`, where the values are a
+/// single list separated by commas, can be read as well. Their hidden values, if any, are read
+/// as values.
+///
+/// Returns an error with the line number if a row can not be parsed.
+pub fn read_labels<T: FromStr>(
+    path: &str,
+    format: &LabelsFormat,
+) -> Result<Vec<Label<T>>, Box<dyn Error>> {
+    format.validate()?;
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(format.delimiter)
+        .flexible(true)
+        .from_reader(compression::open(path)?);
+    let header = rdr.headers()?.clone();
+    let columns = Columns::from_header(&header)
+        .map_err(|reason| format!("{}: invalid header: {}", path, reason))?;
+
+    let mut labels = vec![];
+    for result in rdr.records() {
+        let record = result?;
+        let line = record.position().map_or(0, |position| position.line());
+        let label = columns
+            .parse(&record, format)
+            .map_err(|reason| format!("{}: line {}: {}", path, line, reason))?;
+        labels.push(label);
+    }
+    Ok(labels)
+}
+
+// the layout of the value columns declared by the header
+enum Columns {
+    // one column with all values as a list
+    Legacy,
+    Split {
+        n_values: usize,
+        n_hidden_values: usize,
+    },
+}
+
+impl Columns {
+    fn from_header(header: &csv::StringRecord) -> Result<Columns, String> {
+        let fields = header.iter().collect::<Vec<_>>();
+        if fields.get(..2) != Some(&["node_id", "path"]) {
+            return Err("expected node_id and path as first columns".to_string());
+        }
+        if fields[2..] == ["weights"] {
+            return Ok(Columns::Legacy);
+        }
+        let n_values = fields[2..]
+            .iter()
+            .take_while(|field| field.starts_with("value_"))
+            .count();
+        let n_hidden_values = fields.len() - 2 - n_values;
+        let expected = (0..n_values)
+            .map(|i| format!("value_{}", i))
+            .chain((0..n_hidden_values).map(|i| format!("hidden_value_{}", i)));
+        for (field, expected) in fields[2..].iter().zip(expected) {
+            if *field != expected {
+                return Err(format!("expected column {}, found {}", expected, field));
+            }
+        }
+        Ok(Columns::Split {
+            n_values,
+            n_hidden_values,
+        })
+    }
+
+    fn parse<T: FromStr>(
+        &self,
+        record: &csv::StringRecord,
+        format: &LabelsFormat,
+    ) -> Result<Label<T>, String> {
+        let n_columns = match self {
+            Columns::Legacy => 3,
+            Columns::Split {
+                n_values,
+                n_hidden_values,
+            } => 2 + n_values + n_hidden_values,
+        };
+        if record.len() != n_columns {
+            return Err(format!(
+                "expected {} columns, found {}",
+                n_columns,
+                record.len()
+            ));
+        }
+        let node_id = parse_node_id(&record[0])?;
+        let path = split_path(&record[1], format)?
+            .iter()
+            .map(|node| parse_node_id(node))
+            .collect::<Result<Vec<T>, _>>()?;
+        let (values, hidden_values) = match self {
+            // the older format always separated the values by commas
+            Columns::Legacy => (
+                record[2]
+                    .split(',')
+                    .map(parse_value)
+                    .collect::<Result<_, _>>()?,
+                vec![],
+            ),
+            Columns::Split { n_values, .. } => {
+                let values = record.iter().skip(2).map(parse_value);
+                let mut values = values.collect::<Result<Vec<_>, _>>()?;
+                let hidden_values = values.split_off(*n_values);
+                (values, hidden_values)
+            }
+        };
+        Ok(Label {
+            values,
+            hidden_values,
+            path,
+            node_id,
+            state: 0,
+            line: None,
+            edge: None,
+        })
+    }
+}
+
+fn parse_node_id<T: FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid node id {:?}", s))
+}
+
+fn parse_value(s: &str) -> Result<Weight, String> {
+    s.trim()
+        .parse()
+        .map_err(|_| format!("invalid value {:?}", s))
+}
+
+// splits the path column into node ids, see join_path
+fn split_path(s: &str, format: &LabelsFormat) -> Result<Vec<String>, String> {
+    if s.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(format.list_delimiter)
+        .from_reader(s.as_bytes());
+    let mut records = rdr.records();
+    let record = records
+        .next()
+        .unwrap_or_else(|| Ok(csv::StringRecord::new()))
+        .map_err(|err| format!("invalid path {:?}: {}", s, err))?;
+    if records.next().is_some() {
+        return Err(format!("invalid path {:?}", s));
+    }
+    Ok(record.iter().map(|node| node.to_string()).collect())
+}
@@ -0,0 +1,160 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use crate::bag::{Bag, Label};
+    use crate::mlc::labels::*;
+    use crate::mlc::Bags;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("mlc_test_{}_{}", std::process::id(), name));
+        path.to_str().unwrap().to_string()
+    }
+
+    fn label(node_id: &str, path: &[&str], values: Vec<u64>) -> Label<String> {
+        Label {
+            values,
+            hidden_values: vec![7],
+            path: path.iter().map(|node| node.to_string()).collect(),
+            node_id: node_id.to_string(),
+            state: 0,
+            line: None,
+            edge: None,
+        }
+    }
+
+    #[test]
+    fn test_labels_round_trip() {
+        let odd_ids = ["a|b", "c,d", "e\"f", "g\nh", "", "i\tj"];
+        let labels = vec![
+            label("a|b", &odd_ids, vec![1, 2]),
+            label("c,d", &["c,d"], vec![3, 4]),
+            label("", &[""], vec![5, 6]),
+            label("e\"f", &[], vec![0, 0]),
+        ];
+        let bags: Bags<String> = labels
+            .iter()
+            .map(|label| {
+                let labels = HashSet::from([label.clone()]);
+                (label.node_id.clone(), Bag { labels })
+            })
+            .collect();
+
+        let tab_separated = LabelsFormat {
+            delimiter: b'\t',
+            list_delimiter: b';',
+        };
+        for format in [LabelsFormat::default(), tab_separated] {
+            let path = temp_path("labels.csv");
+            write_bags_with_format(&bags, &path, &format).unwrap();
+            let header = std::fs::read_to_string(&path).unwrap();
+            let delimiter = (format.delimiter as char).to_string();
+            let expected = ["node_id", "path", "value_0", "value_1", "hidden_value_0"];
+            assert!(header.starts_with(&(expected.join(&delimiter) + "\n")));
+
+            let mut read = read_labels::<String>(&path, &format).unwrap();
+            read.sort_by(|a, b| a.values.cmp(&b.values));
+            let mut expected = labels.clone();
+            expected.sort_by(|a, b| a.values.cmp(&b.values));
+            for (read, expected) in read.iter().zip(expected.iter()) {
+                assert_eq!(read.node_id, expected.node_id);
+                assert_eq!(read.path, expected.path);
+                assert_eq!(read.values, expected.values);
+                assert_eq!(read.hidden_values, expected.hidden_values);
+            }
+            assert_eq!(read.len(), labels.len());
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_read_bags_with_hidden_values() {
+        let path = temp_path("hidden.csv");
+        std::fs::write(
+            &path,
+            "node_id|path|value_0|hidden_value_0\n1|0,1|2|5\n1|0,1|3|1\n",
+        )
+        .unwrap();
+        let bags = read_bags(&path).unwrap();
+        // the second label is dominated, as hidden values are not compared
+        let labels = bags[&1].labels.iter().collect::<Vec<_>>();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].values, vec![2]);
+        assert_eq!(labels[0].hidden_values, vec![5]);
+        assert_eq!(labels[0].path, vec![0, 1]);
+        std::fs::remove_file(&path).unwrap();
+
+        // the older format is read as well
+        let bags = read_bags("testdata/results.csv").unwrap();
+        assert_eq!(bags[&4].labels.len(), 16);
+    }
+
+    #[test]
+    fn test_read_labels_errors() {
+        let path = temp_path("invalid.csv");
+        for (content, error) in [
+            (
+                "node_id|path|weights\n0|0|0,0\n1|0,1\n",
+                "line 3: expected 3 columns, found 2",
+            ),
+            (
+                "node_id|path|value_0\n0|0|0\n0|0|x\n",
+                "line 3: invalid value \"x\"",
+            ),
+            (
+                "node_id|path|value_0\na|0|0\n",
+                "line 2: invalid node id \"a\"",
+            ),
+            (
+                "node_id|path|value_1\n",
+                "invalid header: expected column value_0",
+            ),
+            ("path|node_id\n", "invalid header"),
+        ] {
+            std::fs::write(&path, content).unwrap();
+            let err = read_bags(&path).unwrap_err().to_string();
+            assert!(err.contains(error), "{}", err);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_inconsistent_labels() {
+        let mut bags: Bags<String> = HashMap::new();
+        let labels = HashSet::from([label("a", &["a"], vec![1]), label("a", &["a"], vec![1, 2])]);
+        bags.insert("a".to_string(), Bag { labels });
+        assert!(write_bags(&bags, &temp_path("inconsistent.csv")).is_err());
+        assert!(!std::path::Path::new(&temp_path("inconsistent.csv")).exists());
+    }
+
+    #[test]
+    fn test_invalid_formats() {
+        let bags: Bags<String> = HashMap::new();
+        let path = temp_path("invalid_format.csv");
+        for (delimiter, list_delimiter) in [(b',', b','), (b'"', b','), (b'|', b'"'), (b'\n', b',')]
+        {
+            let format = LabelsFormat {
+                delimiter,
+                list_delimiter,
+            };
+            assert!(format.validate().is_err());
+            assert!(write_bags_with_format(&bags, &path, &format).is_err());
+            assert!(read_labels::<String>("testdata/results.csv", &format).is_err());
+        }
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    #[test]
+    fn test_read_legacy_labels_with_list_delimiter() {
+        let path = temp_path("legacy.csv");
+        std::fs::write(&path, "node_id|path|weights\n1|0;1|2,3\n").unwrap();
+        let format = LabelsFormat {
+            delimiter: b'|',
+            list_delimiter: b';',
+        };
+        let labels = read_labels::<usize>(&path, &format).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(labels[0].path, vec![0, 1]);
+        assert_eq!(labels[0].values, vec![2, 3]);
+    }
+}
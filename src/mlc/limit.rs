@@ -1,76 +1,557 @@
 mod test;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::str::FromStr;
 
+use bimap::BiMap;
+
+use crate::bag::Label;
+
+use super::{translate_node, MLCError};
+
+/// Limits per category. The limits of a category are the Pareto set of the values of all labels
+/// that reached the category so far. A category covers a label if one of its limits weakly
+/// dominates the label's values. A label is limited if the covering categories satisfy the
+/// `CoverageRequirement`, by default if every category covers it.
+///
+/// For two criteria, each category is stored as a staircase and the upper envelope over all
+/// staircases is maintained in a tournament tree, so that `is_limit_exceeded` is a single
+/// logarithmic lookup and an update only touches the path from the category to the root. For any
+/// other number of criteria, the Pareto sets are scanned.
 #[derive(Debug)]
 pub struct Limits<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> {
-    pub limits: HashMap<T, Vec<Limit>>,
-    pub limit_cache: HashMap<u64, u64>,
+    dimension: usize,
+    categories: HashMap<T, usize>,
+    // used for two criteria
+    envelope_tree: EnvelopeTree,
+    // used for any other number of criteria
+    pareto_sets: Vec<Vec<Vec<u64>>>,
+    // the labels that achieved the limits, per category
+    origins: Vec<HashMap<Vec<u64>, Label<usize>>>,
+    requirement: CoverageRequirement<T>,
 }
 
-#[derive(Debug)]
+/// Determines how many categories must cover a label before it is limited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoverageRequirement<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> {
+    /// Every category must cover the label.
+    All,
+    /// At least the given number of categories must cover the label. `AtLeast(0)` never limits
+    /// a label, like disabled limits.
+    AtLeast(usize),
+    /// The weights of the covering categories must sum up to at least `required`. Categories
+    /// without a weight count as 0. A `required` of 0 never limits a label.
+    Weighted {
+        weights: HashMap<T, u64>,
+        required: u64,
+    },
+}
+
+/// The categories that cover a label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage<T> {
+    pub covered: Vec<T>,
+    /// The summed weight of the covered categories if the requirement is `Weighted`, otherwise
+    /// the number of covered categories.
+    pub weight: u64,
+    /// True if the covered categories satisfy the requirement, i.e. the label is limited.
+    pub is_satisfied: bool,
+}
+
+/// Limit values per category, used to seed limits before a run.
+pub type Seeds<T> = HashMap<T, Vec<Vec<u64>>>;
+
+/// A point of the Pareto front of a category.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Limit {
-    pub cost: u64,
-    pub time: u64,
+    /// The values of the limit criteria.
+    pub values: Vec<u64>,
+    /// The label that reached the category with these values. Its `node_id` is the node that
+    /// carries the category.
+    pub label: Option<Label<usize>>,
+}
+
+fn weakly_dominates(a: &[u64], b: &[u64]) -> bool {
+    a.iter().zip(b.iter()).all(|(a, b)| a <= b)
+}
+
+/// A non-increasing step function over the first criterion. A step `x -> y` means that the
+/// function is `y` from `x` up to the next step. Before the first step, the function is
+/// `u64::MAX`.
+///
+/// As the Pareto set of a category, the steps are the limits: the smallest second criterion that
+/// was reached with at most `x` in the first criterion.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Staircase {
+    steps: BTreeMap<u64, u64>,
+}
+
+impl Staircase {
+    /// A staircase that is 0 everywhere, the neutral element of `upper_envelope`.
+    fn zero() -> Staircase {
+        Staircase {
+            steps: BTreeMap::from([(0, 0)]),
+        }
+    }
+
+    fn value_at(&self, x: u64) -> u64 {
+        self.steps
+            .range(..=x)
+            .next_back()
+            .map(|(_, y)| *y)
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Inserts the point `(x, y)` and returns the interval `[start, end)` on which the function
+    /// was lowered, or `None` if `(x, y)` is weakly dominated. An `end` of `None` means
+    /// unbounded.
+    fn insert(&mut self, x: u64, y: u64) -> Option<(u64, Option<u64>)> {
+        if self.value_at(x) <= y {
+            return None;
+        }
+        let dominated = self
+            .steps
+            .range(x..)
+            .take_while(|(_, step_y)| **step_y >= y)
+            .map(|(step_x, _)| *step_x)
+            .collect::<Vec<_>>();
+        for step_x in dominated {
+            self.steps.remove(&step_x);
+        }
+        let end = self.steps.range(x..).next().map(|(step_x, _)| *step_x);
+        self.steps.insert(x, y);
+        Some((x, end))
+    }
+
+    fn breakpoints(&self, start: u64, end: Option<u64>) -> impl Iterator<Item = (&u64, &u64)> {
+        self.steps
+            .range((Excluded(start), end.map_or(Unbounded, Excluded)))
+    }
+
+    /// Returns the steps of the pointwise maximum of `a` and `b` on `[start, end)`. The first
+    /// step starts at `start`.
+    fn upper_envelope(
+        a: &Staircase,
+        b: &Staircase,
+        start: u64,
+        end: Option<u64>,
+    ) -> Vec<(u64, u64)> {
+        let mut a_value = a.value_at(start);
+        let mut b_value = b.value_at(start);
+        let mut steps = vec![(start, a_value.max(b_value))];
+
+        let mut a_steps = a.breakpoints(start, end).peekable();
+        let mut b_steps = b.breakpoints(start, end).peekable();
+        loop {
+            let x = match (a_steps.peek(), b_steps.peek()) {
+                (Some((a_x, _)), Some((b_x, _))) => **a_x.min(b_x),
+                (Some((a_x, _)), None) => **a_x,
+                (None, Some((b_x, _))) => **b_x,
+                (None, None) => break,
+            };
+            if let Some((_, y)) = a_steps.next_if(|(a_x, _)| **a_x == x) {
+                a_value = *y;
+            }
+            if let Some((_, y)) = b_steps.next_if(|(b_x, _)| **b_x == x) {
+                b_value = *y;
+            }
+            let max = a_value.max(b_value);
+            if steps.last().unwrap().1 != max {
+                steps.push((x, max));
+            }
+        }
+        steps
+    }
+
+    /// Replaces the function on `[start, end)` by the given steps, the first of which must start
+    /// at `start`. Returns false if nothing changed.
+    fn replace(&mut self, start: u64, end: Option<u64>, steps: Vec<(u64, u64)>) -> bool {
+        let range = (Included(start), end.map_or(Unbounded, Excluded));
+        let unchanged = self.value_at(start) == steps[0].1
+            && self
+                .steps
+                .range(range)
+                .filter(|(x, _)| **x != start)
+                .map(|(x, y)| (*x, *y))
+                .eq(steps[1..].iter().copied());
+        if unchanged {
+            return false;
+        }
+
+        if let Some(end) = end {
+            let value_at_end = self.value_at(end);
+            self.steps.insert(end, value_at_end);
+        }
+        let replaced = self.steps.range(range).map(|(x, _)| *x).collect::<Vec<_>>();
+        for x in replaced {
+            self.steps.remove(&x);
+        }
+        self.steps.extend(steps);
+        true
+    }
+}
+
+/// A complete binary tree of staircases. The leaves are the staircases of the categories, each
+/// inner node is the upper envelope of its two children. Unused leaves are `Staircase::zero`.
+#[derive(Debug, Default)]
+struct EnvelopeTree {
+    // nodes[1] is the root, the children of nodes[i] are nodes[2 * i] and nodes[2 * i + 1]
+    nodes: Vec<Staircase>,
+    n_leaves: usize,
+}
+
+impl EnvelopeTree {
+    fn root(&self) -> Option<&Staircase> {
+        self.nodes.get(1)
+    }
+
+    fn leaf(&self, index: usize) -> &Staircase {
+        &self.nodes[self.n_leaves + index]
+    }
+
+    /// Appends an empty leaf and returns its index.
+    fn push_leaf(&mut self, n_used_leaves: usize) -> usize {
+        if n_used_leaves == self.n_leaves {
+            let n_leaves = (2 * self.n_leaves).max(1);
+            let mut leaves = self.nodes.split_off(self.n_leaves);
+            leaves.resize(n_leaves, Staircase::zero());
+            let mut nodes = vec![Staircase::default(); n_leaves];
+            nodes.extend(leaves);
+            self.nodes = nodes;
+            self.n_leaves = n_leaves;
+            for node in (1..self.n_leaves).rev() {
+                self.update_node(node);
+            }
+        }
+        let mut node = self.n_leaves + n_used_leaves;
+        self.nodes[node] = Staircase::default();
+        while node > 1 {
+            node /= 2;
+            self.update_node(node);
+        }
+        n_used_leaves
+    }
+
+    fn update_node(&mut self, node: usize) {
+        let steps =
+            Staircase::upper_envelope(&self.nodes[2 * node], &self.nodes[2 * node + 1], 0, None);
+        self.nodes[node] = Staircase {
+            steps: steps.into_iter().collect(),
+        };
+    }
+
+    fn insert(&mut self, index: usize, x: u64, y: u64) -> bool {
+        let mut node = self.n_leaves + index;
+        let Some((start, end)) = self.nodes[node].insert(x, y) else {
+            return false;
+        };
+        while node > 1 {
+            node /= 2;
+            let steps = Staircase::upper_envelope(
+                &self.nodes[2 * node],
+                &self.nodes[2 * node + 1],
+                start,
+                end,
+            );
+            if !self.nodes[node].replace(start, end, steps) {
+                break;
+            }
+        }
+        true
+    }
 }
 
 impl<T: std::cmp::Eq + std::hash::Hash + std::marker::Copy> Limits<T> {
-    pub fn new() -> Limits<T> {
+    /// Creates empty limits over `dimension` criteria.
+    pub fn new(dimension: usize) -> Limits<T> {
         Limits {
-            limits: HashMap::new(),
-            limit_cache: HashMap::new(),
+            dimension,
+            categories: HashMap::new(),
+            envelope_tree: EnvelopeTree::default(),
+            pareto_sets: Vec::new(),
+            origins: Vec::new(),
+            requirement: CoverageRequirement::All,
         }
     }
 
+    pub fn set_requirement(&mut self, requirement: CoverageRequirement<T>) {
+        self.requirement = requirement;
+    }
+
+    pub fn requirement(&self) -> &CoverageRequirement<T> {
+        &self.requirement
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    pub fn categories(&self) -> impl Iterator<Item = &T> {
+        self.categories.keys()
+    }
+
     pub fn add_category(&mut self, category: T) {
-        self.limits.insert(category, Vec::new());
-        self.update_limit(category, u64::max_value(), u64::max_value());
+        if self.categories.contains_key(&category) {
+            return;
+        }
+        let index = if self.dimension == 2 {
+            self.envelope_tree.push_leaf(self.categories.len())
+        } else {
+            self.pareto_sets.push(Vec::new());
+            self.pareto_sets.len() - 1
+        };
+        self.origins.push(HashMap::new());
+        self.categories.insert(category, index);
     }
 
     pub fn is_initialized(&self) -> bool {
-        // limits must contain at least category and each category must have at least one limit
-        self.limits.len() > 0 && self.limits.values().all(|v| v.len() > 0)
+        // limits must contain at least one category
+        !self.categories.is_empty()
+    }
+
+    /// Returns the limits of the given category, i.e. its Pareto set, sorted by their values.
+    pub fn limits(&self, category: &T) -> Option<Vec<Limit>> {
+        let index = *self.categories.get(category)?;
+        let mut values = if self.dimension == 2 {
+            self.envelope_tree
+                .leaf(index)
+                .steps
+                .iter()
+                .map(|(x, y)| vec![*x, *y])
+                .collect()
+        } else {
+            self.pareto_sets[index].clone()
+        };
+        values.sort();
+        let limits = values
+            .into_iter()
+            .map(|values| Limit {
+                label: self.origins[index].get(&values).cloned(),
+                values,
+            })
+            .collect();
+        Some(limits)
+    }
+
+    /// Returns the Pareto front of every category.
+    pub fn frontiers(&self) -> HashMap<T, Vec<Limit>> {
+        self.categories
+            .keys()
+            .map(|category| (*category, self.limits(category).unwrap()))
+            .collect()
     }
 
-    pub fn update_limit(&mut self, category: T, cost: u64, time: u64) -> bool {
-        let limit = Limit { cost, time };
-        let limits = self.limits.get_mut(&category).unwrap();
+    pub fn update_limit(&mut self, category: T, values: &[u64]) -> bool {
+        assert_eq!(values.len(), self.dimension);
+        let index = self.categories[&category];
+        if self.dimension == 2 {
+            return self.envelope_tree.insert(index, values[0], values[1]);
+        }
+
+        let limits = &mut self.pareto_sets[index];
         // check if any limit dominates the new limit
-        for l in limits.iter() {
-            if l.cost <= limit.cost && l.time <= limit.time {
-                return false;
-            }
+        if limits.iter().any(|l| weakly_dominates(l, values)) {
+            return false;
         }
         // remove all limits dominated by the new limit
-        limits.retain(|l| l.cost > limit.cost || l.time > limit.time);
+        limits.retain(|l| !weakly_dominates(values, l));
 
-        limits.push(limit);
+        limits.push(values.to_vec());
 
-        self.limit_cache.clear();
+        true
+    }
 
-        return true;
+    /// Like `update_limit`, but remembers the label that achieved the limit.
+    pub fn update_limit_with_label(
+        &mut self,
+        category: T,
+        values: &[u64],
+        label: &Label<usize>,
+    ) -> bool {
+        if !self.update_limit(category, values) {
+            return false;
+        }
+        let origins = &mut self.origins[self.categories[&category]];
+        origins.retain(|limit_values, _| !weakly_dominates(values, limit_values));
+        origins.insert(values.to_vec(), label.clone());
+        true
     }
 
-    /// is_limit_exceeded returns true if each category has a limit that dominates the given cost and time
-    pub fn is_limit_exceeded(&mut self, cost: u64, time: u64) -> bool {
-        if let Some(&limit) = self.limit_cache.get(&cost) {
-            return limit <= time;
+    /// is_limit_exceeded returns true if the categories that have a limit that weakly dominates
+    /// the given values satisfy the coverage requirement
+    pub fn is_limit_exceeded(&self, values: &[u64]) -> bool {
+        match &self.requirement {
+            CoverageRequirement::All if self.dimension == 2 => self
+                .envelope_tree
+                .root()
+                .is_none_or(|root| root.value_at(values[0]) <= values[1]),
+            CoverageRequirement::All => self
+                .categories
+                .values()
+                .all(|index| self.covers(*index, values)),
+            CoverageRequirement::AtLeast(k) => {
+                *k > 0
+                    && self
+                        .categories
+                        .values()
+                        .filter(|index| self.covers(**index, values))
+                        .take(*k)
+                        .count()
+                        >= *k
+            }
+            CoverageRequirement::Weighted { weights, required } => {
+                let mut weight = 0;
+                *required > 0
+                    && self.categories.iter().any(|(category, index)| {
+                        if self.covers(*index, values) {
+                            weight += weights.get(category).copied().unwrap_or(0);
+                        }
+                        weight >= *required
+                    })
+            }
         }
-        let limit = self.determine_limit(cost);
-        self.limit_cache.insert(cost, limit);
-        return limit <= time;
     }
 
-    fn determine_limit(&mut self, cost: u64) -> u64 {
-        let mut min_limits = Vec::new();
-        for limits in self.limits.values() {
-            let mut min_limit = u64::max_value();
-            for limit in limits.iter() {
-                if limit.cost <= cost {
-                    min_limit = std::cmp::min(min_limit, limit.time);
-                }
+    /// Returns the categories that cover the given values.
+    pub fn coverage(&self, values: &[u64]) -> Coverage<T> {
+        let covered = self
+            .categories
+            .iter()
+            .filter(|(_, index)| self.covers(**index, values))
+            .map(|(category, _)| *category)
+            .collect::<Vec<_>>();
+        let (weight, is_satisfied) = match &self.requirement {
+            CoverageRequirement::All => {
+                let weight = covered.len() as u64;
+                (weight, covered.len() == self.categories.len())
             }
-            min_limits.push(min_limit);
+            CoverageRequirement::AtLeast(k) => {
+                let weight = covered.len() as u64;
+                (weight, *k > 0 && covered.len() >= *k)
+            }
+            CoverageRequirement::Weighted { weights, required } => {
+                let weight = covered
+                    .iter()
+                    .map(|category| weights.get(category).copied().unwrap_or(0))
+                    .sum();
+                (weight, *required > 0 && weight >= *required)
+            }
+        };
+        Coverage {
+            covered,
+            weight,
+            is_satisfied,
+        }
+    }
+
+    fn covers(&self, index: usize, values: &[u64]) -> bool {
+        if self.dimension == 2 {
+            self.envelope_tree.leaf(index).value_at(values[0]) <= values[1]
+        } else {
+            self.pareto_sets[index]
+                .iter()
+                .any(|l| weakly_dominates(l, values))
+        }
+    }
+}
+
+/// Writes the Pareto front of every category to a csv file with the columns
+/// `category|node_id|path|limit_values|weights`, where `node_id`, `path` and `weights` describe
+/// the label that achieved the limit and are empty if it is unknown. Fields that contain a
+/// delimiter, a quote or a line break are quoted, so any categories can be written.
+pub fn write_limits<T: Eq + Hash + Copy + Display>(
+    limits: &Limits<T>,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    write_limits_with(limits, path, |node| Ok(node.to_string()))
+}
+
+/// Like `write_limits`, but writes the node ids and paths of the labels as the external node ids
+/// of the node map, see `mlc::translate_bags`.
+pub fn write_translated_limits<T: Eq + Hash + Copy + Display>(
+    limits: &Limits<T>,
+    node_map: &BiMap<String, usize>,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    write_limits_with(limits, path, |node| translate_node(node, node_map))
+}
+
+fn write_limits_with<T: Eq + Hash + Copy + Display>(
+    limits: &Limits<T>,
+    path: &str,
+    node_name: impl Fn(usize) -> Result<String, MLCError>,
+) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::WriterBuilder::new().delimiter(b'|').from_path(path)?;
+    wtr.write_record(["category", "node_id", "path", "limit_values", "weights"])?;
+
+    for (category, limits) in limits.frontiers() {
+        for limit in limits {
+            let (node_id, path, weights) = match &limit.label {
+                Some(label) => {
+                    let mut values = label.values.clone();
+                    values.extend(label.hidden_values.clone());
+                    let path = label
+                        .path
+                        .iter()
+                        .map(|node| node_name(*node))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    (node_name(label.node_id)?, join(&path), join(&values))
+                }
+                None => (String::new(), String::new(), String::new()),
+            };
+            wtr.write_record([
+                category.to_string(),
+                node_id,
+                path,
+                join(&limit.values),
+                weights,
+            ])?;
         }
-        return min_limits.iter().max().unwrap().clone();
     }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Reads the limit values per category from a file written by `write_limits`.
+pub fn read_limits<T>(path: &str) -> Result<Seeds<T>, Box<dyn Error>>
+where
+    T: Eq + Hash + FromStr,
+    T::Err: Error + 'static,
+{
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(b'|')
+        .flexible(true)
+        .from_path(path)?;
+    let mut limits: Seeds<T> = HashMap::new();
+    for result in rdr.records() {
+        let record = result?;
+        if record.len() != 5 {
+            let line = record.position().map_or(0, |position| position.line());
+            return Err(format!(
+                "{}: line {}: expected 5 columns, found {}",
+                path,
+                line,
+                record.len()
+            )
+            .into());
+        }
+        let category = record[0].parse::<T>()?;
+        let values = record[3]
+            .split(',')
+            .map(|s| s.parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()?;
+        limits.entry(category).or_default().push(values);
+    }
+    Ok(limits)
+}
+
+fn join<V: Display>(values: &[V]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
 }
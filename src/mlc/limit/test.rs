@@ -1,72 +1,315 @@
 #[cfg(test)]
 mod tests {
     use super::super::*;
+    use crate::test_util::Lcg;
 
     #[test]
     fn test_update_limits() {
-        let mut limits = Limits::new();
+        let mut limits = Limits::new(2);
 
         limits.add_category("shop");
 
-        assert_eq!(limits.update_limit("shop", 0, 60), true);
-        assert_eq!(limits.update_limit("shop", 0, 70), false);
-        assert_eq!(limits.update_limit("shop", 100, 30), true);
-        assert_eq!(limits.update_limit("shop", 50, 70), false);
-        assert_eq!(limits.update_limit("shop", 50, 50), true);
-        assert_eq!(limits.update_limit("shop", 200, 10), true);
+        assert!(limits.update_limit("shop", &[0, 60]));
+        assert!(!limits.update_limit("shop", &[0, 70]));
+        assert!(limits.update_limit("shop", &[100, 30]));
+        assert!(!limits.update_limit("shop", &[50, 70]));
+        assert!(limits.update_limit("shop", &[50, 50]));
+        assert!(limits.update_limit("shop", &[200, 10]));
     }
 
     #[test]
     fn test_update_limits_single_category() {
-        let mut limits = Limits::new();
+        let mut limits = Limits::new(2);
 
         limits.add_category("shop");
 
-        limits.update_limit("shop", 0, 60);
-        limits.update_limit("shop", 100, 30);
-        limits.update_limit("shop", 50, 50);
-        limits.update_limit("shop", 200, 10);
+        limits.update_limit("shop", &[0, 60]);
+        limits.update_limit("shop", &[100, 30]);
+        limits.update_limit("shop", &[50, 50]);
+        limits.update_limit("shop", &[200, 10]);
 
         println!("{:?}", limits);
-        assert_eq!(limits.is_limit_exceeded(0, 70), true);
-        assert_eq!(limits.is_limit_exceeded(0, 60), true);
-        assert_eq!(limits.is_limit_exceeded(0, 50), false);
+        assert!(limits.is_limit_exceeded(&[0, 70]));
+        assert!(limits.is_limit_exceeded(&[0, 60]));
+        assert!(!limits.is_limit_exceeded(&[0, 50]));
 
-        assert_eq!(limits.is_limit_exceeded(100, 40), true);
-        assert_eq!(limits.is_limit_exceeded(100, 30), true);
-        assert_eq!(limits.is_limit_exceeded(100, 20), false);
+        assert!(limits.is_limit_exceeded(&[100, 40]));
+        assert!(limits.is_limit_exceeded(&[100, 30]));
+        assert!(!limits.is_limit_exceeded(&[100, 20]));
 
-        assert_eq!(limits.is_limit_exceeded(50, 70), true);
-        assert_eq!(limits.is_limit_exceeded(50, 50), true);
-        assert_eq!(limits.is_limit_exceeded(50, 40), false);
+        assert!(limits.is_limit_exceeded(&[50, 70]));
+        assert!(limits.is_limit_exceeded(&[50, 50]));
+        assert!(!limits.is_limit_exceeded(&[50, 40]));
     }
 
     #[test]
     fn test_update_limits_multi_category() {
-        let mut limits = Limits::new();
+        let mut limits = Limits::new(2);
 
         limits.add_category("shop");
 
-        limits.update_limit("shop", 0, 60);
-        limits.update_limit("shop", 100, 30);
-        limits.update_limit("shop", 50, 50);
-        limits.update_limit("shop", 200, 10);
+        limits.update_limit("shop", &[0, 60]);
+        limits.update_limit("shop", &[100, 30]);
+        limits.update_limit("shop", &[50, 50]);
+        limits.update_limit("shop", &[200, 10]);
 
         limits.add_category("grocery");
 
-        limits.update_limit("grocery", 0, 100);
-        limits.update_limit("grocery", 200, 5);
+        limits.update_limit("grocery", &[0, 100]);
+        limits.update_limit("grocery", &[200, 5]);
 
-        assert_eq!(limits.is_limit_exceeded(0, 110), true); // border determined by (0, 100)
-        assert_eq!(limits.is_limit_exceeded(0, 100), true);
-        assert_eq!(limits.is_limit_exceeded(0, 90), false);
+        assert!(limits.is_limit_exceeded(&[0, 110])); // border determined by (0, 100)
+        assert!(limits.is_limit_exceeded(&[0, 100]));
+        assert!(!limits.is_limit_exceeded(&[0, 90]));
 
-        assert_eq!(limits.is_limit_exceeded(200, 20), true); // border determined by (200, 10)
-        assert_eq!(limits.is_limit_exceeded(200, 10), true);
-        assert_eq!(limits.is_limit_exceeded(200, 5), false);
+        assert!(limits.is_limit_exceeded(&[200, 20])); // border determined by (200, 10)
+        assert!(limits.is_limit_exceeded(&[200, 10]));
+        assert!(!limits.is_limit_exceeded(&[200, 5]));
 
-        assert_eq!(limits.is_limit_exceeded(100, 110), true); // border determined by (0, 100)
-        assert_eq!(limits.is_limit_exceeded(100, 100), true);
-        assert_eq!(limits.is_limit_exceeded(100, 90), false);
+        assert!(limits.is_limit_exceeded(&[100, 110])); // border determined by (0, 100)
+        assert!(limits.is_limit_exceeded(&[100, 100]));
+        assert!(!limits.is_limit_exceeded(&[100, 90]));
+    }
+
+    #[test]
+    fn test_update_limits_three_criteria() {
+        let mut limits = Limits::new(3);
+
+        limits.add_category("shop");
+
+        assert!(limits.update_limit("shop", &[0, 10, 60]));
+        assert!(limits.update_limit("shop", &[10, 0, 60]));
+        assert!(!limits.update_limit("shop", &[10, 10, 60]));
+        assert!(limits.update_limit("shop", &[10, 10, 50]));
+
+        assert!(limits.is_limit_exceeded(&[0, 10, 60]));
+        assert!(!limits.is_limit_exceeded(&[0, 10, 59]));
+        assert!(limits.is_limit_exceeded(&[10, 10, 50]));
+        assert!(!limits.is_limit_exceeded(&[10, 9, 50]));
+        assert!(limits.is_limit_exceeded(&[10, 9, 60]));
+        assert!(!limits.is_limit_exceeded(&[5, 5, 100]));
+    }
+
+    #[test]
+    fn test_update_limits_three_criteria_multi_category() {
+        let mut limits = Limits::new(3);
+
+        limits.add_category("shop");
+        limits.add_category("school");
+
+        limits.update_limit("shop", &[10, 0, 5]);
+        assert!(!limits.is_limit_exceeded(&[10, 0, 5])); // school not reached yet
+
+        limits.update_limit("school", &[5, 5, 0]);
+        assert!(!limits.is_limit_exceeded(&[10, 0, 5])); // not dominated for school
+        assert!(limits.is_limit_exceeded(&[10, 5, 5]));
+        assert!(!limits.is_limit_exceeded(&[10, 5, 4]));
+
+        limits.update_limit("school", &[0, 0, 0]);
+        assert!(limits.is_limit_exceeded(&[10, 0, 5]));
+        assert!(!limits.is_limit_exceeded(&[9, 100, 100]));
+    }
+
+    #[test]
+    fn test_envelope_matches_pareto_sets() {
+        let mut rng = Lcg(42);
+
+        let n_categories = 20;
+        let mut limits = Limits::new(2);
+        let mut pareto_sets: Vec<Vec<(u64, u64)>> = vec![vec![]; n_categories];
+        for category in 0..n_categories {
+            limits.add_category(category);
+        }
+
+        for _ in 0..2000 {
+            let category = rng.next(n_categories as u64) as usize;
+            let (x, y) = (rng.next(100), rng.next(100));
+            let updated = limits.update_limit(category, &[x, y]);
+
+            let pareto_set = &mut pareto_sets[category];
+            let dominated = pareto_set.iter().any(|(a, b)| *a <= x && *b <= y);
+            assert_eq!(updated, !dominated);
+            if !dominated {
+                pareto_set.retain(|(a, b)| *a < x || *b < y);
+                pareto_set.push((x, y));
+            }
+
+            for _ in 0..10 {
+                let (x, y) = (rng.next(110), rng.next(110));
+                let expected = pareto_sets
+                    .iter()
+                    .all(|pareto_set| pareto_set.iter().any(|(a, b)| *a <= x && *b <= y));
+                assert_eq!(limits.is_limit_exceeded(&[x, y]), expected);
+            }
+        }
+
+        for (category, pareto_set) in pareto_sets.iter().enumerate() {
+            let mut expected = pareto_set.clone();
+            expected.sort();
+            let actual = limits
+                .limits(&category)
+                .unwrap()
+                .iter()
+                .map(|l| (l.values[0], l.values[1]))
+                .collect::<Vec<_>>();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_add_category_after_updates() {
+        let mut limits = Limits::new(2);
+
+        limits.add_category("shop");
+        limits.update_limit("shop", &[0, 60]);
+        assert!(limits.is_limit_exceeded(&[0, 60]));
+
+        limits.add_category("grocery");
+        assert!(!limits.is_limit_exceeded(&[0, 60]));
+
+        limits.update_limit("grocery", &[10, 10]);
+        assert!(!limits.is_limit_exceeded(&[0, 60]));
+        assert!(limits.is_limit_exceeded(&[10, 60]));
+        assert!(!limits.is_limit_exceeded(&[10, 59]));
+    }
+
+    #[test]
+    fn test_update_limit_with_label() {
+        let mut limits = Limits::new(2);
+        limits.add_category("shop");
+
+        let label = |values: Vec<u64>, node_id: usize| Label {
+            values,
+            hidden_values: vec![],
+            path: vec![0],
+            node_id,
+            state: 0,
+            line: None,
+            edge: None,
+        };
+
+        assert!(limits.update_limit_with_label("shop", &[0, 60], &label(vec![0, 60], 1)));
+        assert!(limits.update_limit_with_label("shop", &[50, 50], &label(vec![50, 50], 2)));
+        assert!(!limits.update_limit_with_label("shop", &[50, 60], &label(vec![50, 60], 3)));
+        assert!(limits.update_limit_with_label("shop", &[0, 50], &label(vec![0, 50], 4)));
+
+        let shop_limits = limits.limits(&"shop").unwrap();
+        assert_eq!(shop_limits.len(), 1);
+        assert_eq!(shop_limits[0].values, vec![0, 50]);
+        assert_eq!(shop_limits[0].label.as_ref().unwrap().node_id, 4);
+        assert_eq!(limits.origins[0].len(), 1);
+    }
+
+    #[test]
+    fn test_coverage_requirements() {
+        for dimension in [2, 3] {
+            let mut limits = Limits::new(dimension);
+            for category in ["shop", "school", "doctor"] {
+                limits.add_category(category);
+            }
+            let values = |cost: u64, time: u64| {
+                let mut values = vec![cost, time];
+                values.resize(dimension, 0);
+                values
+            };
+            limits.update_limit("shop", &values(0, 10));
+            limits.update_limit("school", &values(0, 20));
+
+            assert!(!limits.is_limit_exceeded(&values(0, 20)));
+            let coverage = limits.coverage(&values(0, 20));
+            assert_eq!(coverage.weight, 2);
+            assert!(!coverage.is_satisfied);
+
+            limits.set_requirement(CoverageRequirement::AtLeast(2));
+            assert!(limits.is_limit_exceeded(&values(0, 20)));
+            assert!(!limits.is_limit_exceeded(&values(0, 15)));
+            assert!(limits.coverage(&values(0, 20)).is_satisfied);
+
+            // no categories are required, so nothing is limited
+            limits.set_requirement(CoverageRequirement::AtLeast(0));
+            assert!(!limits.is_limit_exceeded(&values(0, 20)));
+            assert!(!limits.coverage(&values(0, 20)).is_satisfied);
+
+            limits.set_requirement(CoverageRequirement::Weighted {
+                weights: HashMap::from([("shop", 1), ("school", 2), ("doctor", 3)]),
+                required: 3,
+            });
+            assert!(limits.is_limit_exceeded(&values(0, 20)));
+            assert!(!limits.is_limit_exceeded(&values(0, 10)));
+            let mut coverage = limits.coverage(&values(0, 10));
+            assert_eq!(coverage.covered, vec!["shop"]);
+            assert_eq!(coverage.weight, 1);
+
+            limits.update_limit("doctor", &values(0, 10));
+            assert!(limits.is_limit_exceeded(&values(0, 10)));
+            coverage = limits.coverage(&values(0, 10));
+            coverage.covered.sort();
+            assert_eq!(coverage.covered, vec!["doctor", "shop"]);
+            assert_eq!(coverage.weight, 4);
+        }
+    }
+
+    #[test]
+    fn test_write_and_read_limits_with_delimiters_in_categories() {
+        let mut limits = Limits::new(2);
+        limits.add_category("shop|grocery");
+        limits.add_category("school, \"primary\"");
+        limits.update_limit("shop|grocery", &[0, 60]);
+        limits.update_limit("shop|grocery", &[50, 50]);
+        limits.update_limit("school, \"primary\"", &[10, 20]);
+
+        let path = std::env::temp_dir().join(format!(
+            "mlc_test_limits_quoting_{}.csv",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        write_limits(&limits, path).unwrap();
+        let mut seeds = read_limits::<String>(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        for values in seeds.values_mut() {
+            values.sort();
+        }
+        assert_eq!(
+            seeds,
+            HashMap::from([
+                ("shop|grocery".to_string(), vec![vec![0, 60], vec![50, 50]]),
+                ("school, \"primary\"".to_string(), vec![vec![10, 20]]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_write_translated_limits() {
+        let mut limits = Limits::new(2);
+        limits.add_category("shop");
+        let label = Label {
+            values: vec![1, 2],
+            hidden_values: vec![],
+            path: vec![0, 1],
+            node_id: 1,
+            state: 0,
+            line: None,
+            edge: None,
+        };
+        limits.update_limit_with_label("shop", &[1, 2], &label);
+
+        let path = std::env::temp_dir().join(format!(
+            "mlc_test_translated_limits_{}.csv",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        let mut node_map = BiMap::new();
+        node_map.insert("a".to_string(), 0);
+        assert!(write_translated_limits(&limits, &node_map, path).is_err());
+
+        node_map.insert("b".to_string(), 1);
+        write_translated_limits(&limits, &node_map, path).unwrap();
+        let written = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(
+            written,
+            "category|node_id|path|limit_values|weights\nshop|b|a,b|1,2|1,2\n"
+        );
     }
 }
@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant};
+
+use log::{error, info};
+
+use super::{write_bags, Bags};
+
+/// A snapshot of the state of a running MLC search.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    /// Number of labels popped from the queue and expanded so far.
+    pub labels_processed: usize,
+    /// Number of labels currently waiting in the queue.
+    pub queue_size: usize,
+    /// Total number of labels in all bags.
+    pub total_bag_size: usize,
+    /// Time since the start of `run`.
+    pub elapsed: Duration,
+}
+
+/// Receives periodic callbacks from `MLC::run`.
+///
+/// `on_progress` is called every `progress_interval` processed labels (see
+/// `MLC::set_progress_interval`), `on_finish` once after the queue ran empty.
+pub trait ProgressObserver {
+    fn on_progress(&mut self, progress: &Progress, bags: &Bags<usize>);
+
+    fn on_finish(&mut self, _progress: &Progress, _bags: &Bags<usize>) {}
+}
+
+/// Logs the progress at info level.
+pub struct LogObserver;
+
+impl ProgressObserver for LogObserver {
+    fn on_progress(&mut self, progress: &Progress, _bags: &Bags<usize>) {
+        info!(
+            "processed {} labels, queue size: {}, total bag size: {}, elapsed: {}ms",
+            progress.labels_processed,
+            progress.queue_size,
+            progress.total_bag_size,
+            progress.elapsed.as_millis()
+        );
+    }
+}
+
+/// Writes the current bags to a labels file, at most once per `interval`.
+/// The final bags are always written when the run finishes.
+pub struct SnapshotObserver {
+    path: String,
+    interval: Duration,
+    last_write: Instant,
+}
+
+impl SnapshotObserver {
+    pub fn new(path: &str, interval: Duration) -> SnapshotObserver {
+        SnapshotObserver {
+            path: path.to_string(),
+            interval,
+            last_write: Instant::now(),
+        }
+    }
+
+    fn write(&mut self, bags: &Bags<usize>) {
+        info!("writing labels to {}", self.path);
+        if let Err(err) = write_bags(bags, &self.path) {
+            error!("failed to write labels to {}: {}", self.path, err);
+        }
+        self.last_write = Instant::now();
+    }
+}
+
+impl ProgressObserver for SnapshotObserver {
+    fn on_progress(&mut self, _progress: &Progress, bags: &Bags<usize>) {
+        if self.last_write.elapsed() > self.interval {
+            self.write(bags);
+        }
+    }
+
+    fn on_finish(&mut self, _progress: &Progress, bags: &Bags<usize>) {
+        self.write(bags);
+    }
+}
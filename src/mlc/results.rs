@@ -0,0 +1,198 @@
+mod test;
+
+use std::cmp::Ordering;
+
+use bimap::BiMap;
+
+use crate::bag::{Criterion, Label, NodeId, Weight};
+
+use super::{Bags, MLCError};
+
+/// How `Results::best` ranks the labels of a node.
+#[derive(Debug, Clone)]
+pub enum Preference {
+    /// Compare the criteria in the given order, then the remaining values in order.
+    Lexicographic(Vec<Criterion>),
+    /// Compare the sum of the values multiplied by one weight per value.
+    WeightedSum(Vec<f64>),
+}
+
+/// Read-only queries over the bags of a finished run, see `MLC::results`.
+///
+/// Labels are returned in a deterministic order: by the requested key, then by their values and
+/// paths. Nodes that were not reached have no labels.
+#[derive(Debug, Clone)]
+pub struct Results<'a> {
+    bags: Bags<usize>,
+    node_map: Option<&'a BiMap<String, usize>>,
+}
+
+impl<'a> Results<'a> {
+    /// Wraps bags, e.g. read by `read_bags` or returned by `MLC::accepted_bags`. The node map is
+    /// only needed for `translated_path`.
+    pub fn new(bags: Bags<usize>, node_map: Option<&'a BiMap<String, usize>>) -> Self {
+        Results { bags, node_map }
+    }
+
+    pub fn bags(&self) -> &Bags<usize> {
+        &self.bags
+    }
+
+    /// Returns the labels of the node, ordered by their values.
+    pub fn labels(&self, node: NodeId) -> Vec<&Label<usize>> {
+        let mut labels = self
+            .bags
+            .get(&node)
+            .map(|bag| bag.labels.iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        labels.sort_by(|a, b| by_values(a, b));
+        labels
+    }
+
+    /// Returns the Pareto front of the node by values, ordered by the criterion. Of labels with
+    /// equal values, the first by paths is kept.
+    pub fn front(
+        &self,
+        node: NodeId,
+        criterion: Criterion,
+    ) -> Result<Vec<&Label<usize>>, MLCError> {
+        self.check_criterion(criterion)?;
+        // a label can only be dominated by labels before it
+        let mut front: Vec<&Label<usize>> = vec![];
+        for label in self.labels(node) {
+            let dominated = front.iter().any(|other| {
+                other
+                    .values
+                    .iter()
+                    .zip(label.values.iter())
+                    .all(|(a, b)| a <= b)
+            });
+            if !dominated {
+                front.push(label);
+            }
+        }
+        front.sort_by_key(|label| label.criterion(criterion));
+        Ok(front)
+    }
+
+    /// Returns the best label of the node by the preference, or `None` if the node was not
+    /// reached.
+    pub fn best(
+        &self,
+        node: NodeId,
+        preference: &Preference,
+    ) -> Result<Option<&Label<usize>>, MLCError> {
+        let labels = self.labels(node);
+        match preference {
+            Preference::Lexicographic(criteria) => {
+                for criterion in criteria {
+                    self.check_criterion(*criterion)?;
+                }
+                let key = |label: &Label<usize>| {
+                    criteria
+                        .iter()
+                        .map(|criterion| label.criterion(*criterion))
+                        .collect::<Vec<_>>()
+                };
+                Ok(labels.into_iter().min_by_key(|label| key(label)))
+            }
+            Preference::WeightedSum(weights) => {
+                if let Some(label) = labels.first() {
+                    if weights.len() != label.values.len() {
+                        return Err(MLCError::InvalidPreference(format!(
+                            "expected {} weights, got {}",
+                            label.values.len(),
+                            weights.len()
+                        )));
+                    }
+                }
+                let sum = |label: &Label<usize>| -> f64 {
+                    label
+                        .values
+                        .iter()
+                        .zip(weights.iter())
+                        .map(|(value, weight)| *value as f64 * weight)
+                        .sum()
+                };
+                // labels are ordered by their values, so ties keep the first one
+                Ok(labels
+                    .into_iter()
+                    .min_by(|a, b| sum(a).partial_cmp(&sum(b)).unwrap_or(Ordering::Equal)))
+            }
+        }
+    }
+
+    /// Returns the labels of the node whose value of each criterion is at most its bound,
+    /// ordered by their values.
+    pub fn within_bounds(
+        &self,
+        node: NodeId,
+        bounds: &[(Criterion, Weight)],
+    ) -> Result<Vec<&Label<usize>>, MLCError> {
+        for (criterion, _) in bounds {
+            self.check_criterion(*criterion)?;
+        }
+        Ok(self
+            .labels(node)
+            .into_iter()
+            .filter(|label| is_within(label, bounds))
+            .collect())
+    }
+
+    /// Returns the nodes with at least one label within the bounds, see `within_bounds`,
+    /// ordered by node id, with these labels.
+    pub fn reachable_within<'b>(
+        &'b self,
+        bounds: &'b [(Criterion, Weight)],
+    ) -> Result<impl Iterator<Item = (NodeId, Vec<&'b Label<usize>>)> + 'b, MLCError> {
+        for (criterion, _) in bounds {
+            self.check_criterion(*criterion)?;
+        }
+        let mut nodes = self.bags.keys().copied().collect::<Vec<_>>();
+        nodes.sort();
+        Ok(nodes.into_iter().filter_map(move |node| {
+            let labels = self
+                .labels(node)
+                .into_iter()
+                .filter(|label| is_within(label, bounds))
+                .collect::<Vec<_>>();
+            (!labels.is_empty()).then_some((node, labels))
+        }))
+    }
+
+    /// Returns the path of the label as external node ids.
+    pub fn translated_path(&self, label: &Label<usize>) -> Result<Vec<String>, MLCError> {
+        let node_map = self.node_map.ok_or(MLCError::NodeMapNotSet)?;
+        label
+            .path
+            .iter()
+            .map(|node| {
+                node_map
+                    .get_by_right(node)
+                    .cloned()
+                    .ok_or(MLCError::UnknownNodeId(*node))
+            })
+            .collect()
+    }
+
+    // the criterion must exist for the labels; any label will do, as all have the same lengths
+    fn check_criterion(&self, criterion: Criterion) -> Result<(), MLCError> {
+        let label = self.bags.values().find_map(|bag| bag.labels.iter().next());
+        match label {
+            Some(label) if !criterion.is_valid(label.values.len(), label.hidden_values.len()) => {
+                Err(MLCError::InvalidCriterion(criterion))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn by_values(a: &Label<usize>, b: &Label<usize>) -> Ordering {
+    (&a.values, &a.hidden_values, &a.path).cmp(&(&b.values, &b.hidden_values, &b.path))
+}
+
+fn is_within(label: &Label<usize>, bounds: &[(Criterion, Weight)]) -> bool {
+    bounds
+        .iter()
+        .all(|(criterion, bound)| label.criterion(*criterion) <= *bound)
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use crate::bag::Criterion;
+    use crate::mlc::automaton::Automaton;
+    use crate::mlc::results::{Preference, Results};
+    use crate::mlc::{MLCError, MLC};
+    use crate::read;
+
+    #[test]
+    fn test_results() {
+        let (g, node_map) = read::read_graph_and_reset_ids("testdata/edges.csv").unwrap();
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_node_map(node_map.clone());
+        let start = *node_map.get_by_left("0").unwrap();
+        let node = *node_map.get_by_left("3").unwrap();
+        mlc.set_start_node(start);
+        mlc.run().unwrap();
+        let results = mlc.results();
+
+        let values = |labels: Vec<&crate::bag::Label<usize>>| {
+            labels
+                .into_iter()
+                .map(|label| label.values.clone())
+                .collect::<Vec<_>>()
+        };
+        let front = results.front(node, Criterion::Value(1)).unwrap();
+        assert_eq!(front.len(), 8);
+        assert_eq!(front[0].values, vec![7, 0]);
+        assert_eq!(front[7].values, vec![0, 7]);
+        assert!(matches!(
+            results.front(node, Criterion::Value(2)),
+            Err(MLCError::InvalidCriterion(Criterion::Value(2)))
+        ));
+
+        let best = |preference| results.best(node, &preference).unwrap().unwrap();
+        let lexicographic = Preference::Lexicographic(vec![Criterion::Value(1)]);
+        assert_eq!(best(lexicographic).values, vec![7, 0]);
+        assert_eq!(best(Preference::Lexicographic(vec![])).values, vec![0, 7]);
+        assert_eq!(
+            best(Preference::WeightedSum(vec![1.0, 2.0])).values,
+            vec![7, 0]
+        );
+        // all labels have the same sum, so the first by values is kept
+        assert_eq!(
+            best(Preference::WeightedSum(vec![1.0, 1.0])).values,
+            vec![0, 7]
+        );
+        assert!(results
+            .best(node, &Preference::WeightedSum(vec![1.0]))
+            .is_err());
+        assert_eq!(
+            results
+                .best(99, &Preference::Lexicographic(vec![]))
+                .unwrap(),
+            None
+        );
+
+        let bounds = [(Criterion::Value(0), 2), (Criterion::Value(1), 6)];
+        assert_eq!(
+            values(results.within_bounds(node, &bounds).unwrap()),
+            vec![vec![1, 6], vec![2, 5]]
+        );
+        let reachable = results
+            .reachable_within(&bounds)
+            .unwrap()
+            .collect::<Vec<_>>();
+        // nodes 0 to 3, as every label of node 4 has a sum of 15
+        assert_eq!(reachable.len(), 4);
+        assert!(reachable.windows(2).all(|w| w[0].0 < w[1].0));
+
+        let label = results.labels(node)[0];
+        assert_eq!(
+            results.translated_path(label).unwrap(),
+            vec!["0", "1", "2", "3"]
+        );
+        let untranslated = Results::new(results.bags().clone(), None);
+        assert!(matches!(
+            untranslated.translated_path(label),
+            Err(MLCError::NodeMapNotSet)
+        ));
+    }
+
+    #[test]
+    fn test_results_with_automaton() {
+        let g = read::read_graph_with_int_ids("testdata/edges_modes.csv").unwrap();
+        // walk (0), then transit (1), then walk, using transit at least once
+        let mut automaton = Automaton::new(3);
+        automaton.add_transition(0, 0, 0);
+        automaton.add_transition(0, 1, 1);
+        automaton.add_transition(1, 1, 1);
+        automaton.add_transition(1, 0, 2);
+        automaton.add_transition(2, 0, 2);
+        automaton.set_accepting(1);
+        automaton.set_accepting(2);
+
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_automaton(automaton);
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap().clone();
+        assert_eq!(bags[&3].labels.len(), 3);
+
+        // the label of state 0 is not accepted, and the one of state 1 is dominated by state 2
+        let results = mlc.results();
+        let front = results.front(3, Criterion::Value(0)).unwrap();
+        assert_eq!(front.len(), 1);
+        assert_eq!(front[0].values, vec![3, 3]);
+        assert_eq!(front[0].path, vec![0, 1, 2, 3]);
+
+        // over the raw bags, the labels of other states are dominated by values
+        let raw = Results::new(bags, None);
+        assert_eq!(raw.labels(3).len(), 3);
+        let front = raw.front(3, Criterion::Value(0)).unwrap();
+        assert_eq!(front.len(), 1);
+        assert_eq!(front[0].values, vec![1, 1]);
+    }
+}
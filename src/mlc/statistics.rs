@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+/// Counters and timings collected during `MLC::run`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// Number of labels popped from the queue.
+    pub labels_popped: usize,
+    /// Number of popped labels that were no longer in their bag.
+    pub labels_skipped_stale: usize,
+    /// Number of labels created along an edge.
+    pub labels_created: usize,
+    /// Number of created labels that were dominated by the target bag.
+    pub labels_rejected_dominance: usize,
+    /// Number of labels removed from a bag because a new label dominated them.
+    pub labels_removed_from_bags: usize,
+    /// Number of popped labels that were discarded because they exceeded the limit.
+    pub limit_discards: usize,
+    /// Maximum length of the queue.
+    pub peak_queue_length: usize,
+    /// Maximum number of labels in all bags.
+    pub peak_total_labels: usize,
+    pub timings: Timings,
+}
+
+/// Time spent in the phases of `MLC::run`. The phases are only measured if enabled with
+/// `MLC::set_enable_timings`, the whole run always.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Checking popped labels against the limits.
+    pub limit_check: Duration,
+    /// Checking whether popped labels are still in their bag.
+    pub stale_check: Duration,
+    /// Creating new labels along the outgoing edges and adding them to the target bags.
+    pub expansion: Duration,
+    /// The whole run.
+    pub total: Duration,
+}
@@ -1,7 +1,27 @@
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    use bimap::BiMap;
+    use petgraph::graph::EdgeIndex;
+
+    use crate::bag::{Bag, Criterion, Label, WeightsTuple};
     use crate::mlc;
+    use crate::mlc::automaton::Automaton;
+    use crate::mlc::progress::{Progress, ProgressObserver};
+    use crate::mlc::Bags;
     use crate::read;
+    use crate::read::MLCGraph;
+
+    fn weights(weights: Vec<u64>) -> WeightsTuple {
+        WeightsTuple {
+            weights,
+            hidden_weights: vec![],
+            ..Default::default()
+        }
+    }
 
     #[test]
     fn test_run_mlc() {
@@ -13,4 +33,409 @@ mod tests {
         let expected_result = mlc::read_bags("testdata/results.csv").unwrap();
         assert!(bags == &expected_result);
     }
+
+    struct RecordingObserver {
+        progress: Rc<RefCell<Vec<Progress>>>,
+        finished: Rc<RefCell<Option<Progress>>>,
+    }
+
+    impl ProgressObserver for RecordingObserver {
+        fn on_progress(&mut self, progress: &Progress, _bags: &Bags<usize>) {
+            self.progress.borrow_mut().push(progress.clone());
+        }
+
+        fn on_finish(&mut self, progress: &Progress, _bags: &Bags<usize>) {
+            *self.finished.borrow_mut() = Some(progress.clone());
+        }
+    }
+
+    #[test]
+    fn test_progress_observer() {
+        let g = read::read_graph_with_int_ids("testdata/edges.csv").unwrap();
+        let progress = Rc::new(RefCell::new(vec![]));
+        let finished = Rc::new(RefCell::new(None));
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_progress_observer(Box::new(RecordingObserver {
+            progress: progress.clone(),
+            finished: finished.clone(),
+        }));
+        mlc.set_progress_interval(1);
+        mlc.set_start_node(0);
+        mlc.run().unwrap();
+
+        let progress = progress.borrow();
+        assert_eq!(progress.len(), 31);
+        assert_eq!(
+            progress
+                .iter()
+                .map(|p| p.labels_processed)
+                .collect::<Vec<_>>(),
+            (1..=31).collect::<Vec<_>>()
+        );
+        let finished = finished.borrow();
+        let finished = finished.as_ref().unwrap();
+        assert_eq!(finished.labels_processed, 31);
+        assert_eq!(finished.queue_size, 0);
+        assert_eq!(finished.total_bag_size, 31);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let g = MLCGraph::<()>::from_edges([
+            (0, 1, weights(vec![2, 2])),
+            (0, 2, weights(vec![1, 1])),
+            (2, 1, weights(vec![0, 0])),
+            (1, 0, weights(vec![5, 5])),
+        ]);
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        mlc.run().unwrap();
+
+        let stats = mlc.statistics();
+        assert_eq!(stats.labels_popped, 4);
+        assert_eq!(stats.labels_skipped_stale, 1);
+        assert_eq!(stats.labels_created, 4);
+        assert_eq!(stats.labels_rejected_dominance, 1);
+        assert_eq!(stats.labels_removed_from_bags, 1);
+        assert_eq!(stats.limit_discards, 0);
+        assert_eq!(stats.peak_queue_length, 2);
+        assert_eq!(stats.peak_total_labels, 3);
+        // phases are only timed if enabled
+        assert_eq!(stats.timings.expansion, std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn test_limit_with_three_criteria() {
+        let mut g = MLCGraph::<&str>::new();
+        let nodes = [vec![], vec!["shop"], vec![], vec![], vec![], vec![]]
+            .into_iter()
+            .map(|categories| g.add_node(categories))
+            .collect::<Vec<_>>();
+        for (u, v, w) in [
+            (0, 1, vec![1, 1, 1]),
+            (0, 2, vec![2, 2, 2]),
+            (2, 3, vec![0, 0, 0]),
+            (0, 4, vec![0, 5, 0]),
+            (4, 5, vec![0, 0, 0]),
+        ] {
+            g.add_edge(nodes[u], nodes[v], weights(w));
+        }
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        assert!(mlc
+            .set_limit_criteria(vec![Criterion::Value(0), Criterion::Value(3)])
+            .is_err());
+        mlc.set_enable_limit(true);
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+
+        assert!(bags.contains_key(&5));
+        assert!(!bags.contains_key(&3));
+        assert_eq!(mlc.statistics().limit_discards, 2);
+        let label = |values: Vec<u64>| Label {
+            values,
+            hidden_values: vec![],
+            path: vec![],
+            node_id: 3,
+            state: 0,
+            line: None,
+            edge: None,
+        };
+        let coverage = mlc.limit_coverage(&label(vec![2, 2, 2]));
+        assert_eq!(coverage.covered, vec!["shop"]);
+        assert!(coverage.is_satisfied);
+        assert!(mlc.limit_coverage(&label(vec![0, 5, 0])).covered.is_empty());
+
+        let shop_limits = mlc.limits().limits(&"shop").unwrap();
+        assert_eq!(shop_limits.len(), 1);
+        assert_eq!(shop_limits[0].values, vec![1, 1, 1]);
+        let label = shop_limits[0].label.as_ref().unwrap();
+        assert_eq!(label.node_id, 1);
+    }
+
+    fn category_graph() -> MLCGraph<&'static str> {
+        let mut g = MLCGraph::<&str>::new();
+        let nodes = [vec![], vec!["shop"], vec![], vec!["school"], vec![]]
+            .into_iter()
+            .map(|categories| g.add_node(categories))
+            .collect::<Vec<_>>();
+        for (u, v, w) in [
+            (0, 1, vec![1, 5]),
+            (0, 1, vec![5, 1]),
+            (1, 2, vec![1, 1]),
+            (0, 2, vec![10, 10]),
+            (2, 3, vec![2, 2]),
+            (3, 4, vec![1, 1]),
+            (0, 4, vec![2, 9]),
+        ] {
+            g.add_edge(nodes[u], nodes[v], weights(w));
+        }
+        g
+    }
+
+    #[test]
+    fn test_seed_limits() {
+        let g = category_graph();
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_enable_limit(true);
+        mlc.set_start_node(0);
+        let expected_bags = mlc.run().unwrap().clone();
+        let frontiers = mlc.limits().frontiers();
+        let n_limit_discards = mlc.statistics().limit_discards;
+
+        let path =
+            std::env::temp_dir().join(format!("mlc_test_seed_limits_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        mlc::limit::write_limits(mlc.limits(), path).unwrap();
+        let seeds = mlc::limit::read_limits::<String>(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(seeds["shop"], vec![vec![1, 5], vec![5, 1]]);
+        assert_eq!(seeds["school"], vec![vec![4, 8], vec![8, 4]]);
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_enable_limit(true);
+        mlc.seed_limits_from(&frontiers, &[0, 0]).unwrap();
+        mlc.set_start_node(0);
+        assert_eq!(mlc.run().unwrap(), &expected_bags);
+        assert_eq!(mlc.statistics().limit_discards, n_limit_discards);
+        assert!(mlc.unverified_seeds().is_empty());
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_enable_limit(true);
+        assert!(mlc
+            .seed_limits(HashMap::from([("shop", vec![vec![0]])]))
+            .is_err());
+        assert!(mlc
+            .seed_limits(HashMap::from([("library", vec![vec![0, 0]])]))
+            .is_err());
+        mlc.seed_limits(HashMap::from([("shop", vec![vec![0, 0]])]))
+            .unwrap();
+        assert!(mlc.set_limit_criteria(vec![Criterion::Value(0)]).is_err());
+        mlc.set_start_node(0);
+        mlc.run().unwrap();
+        assert_eq!(mlc.unverified_seeds(), vec![("shop", vec![0, 0])]);
+
+        // seeds are not checked, so unsafe seeds prune labels that would be part of the result
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_enable_limit(true);
+        mlc.seed_limits(HashMap::from([
+            ("shop", vec![vec![1, 1]]),
+            ("school", vec![vec![1, 1]]),
+        ]))
+        .unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        assert_eq!(bags.len(), 4);
+        assert_eq!(values(&bags[&1]), vec![vec![1, 5], vec![5, 1]]);
+        assert_eq!(values(&bags[&2]), vec![vec![10, 10]]);
+        assert_eq!(values(&bags[&4]), vec![vec![2, 9]]);
+        assert_eq!(mlc.statistics().limit_discards, 4);
+        assert_eq!(mlc.unverified_seeds().len(), 2);
+    }
+
+    #[test]
+    fn test_tight_seed_limits() {
+        let mut g = MLCGraph::<&str>::new();
+        let nodes = [vec![], vec![], vec!["shop"]]
+            .into_iter()
+            .map(|categories| g.add_node(categories))
+            .collect::<Vec<_>>();
+        g.add_edge(nodes[0], nodes[1], weights(vec![1, 1]));
+        g.add_edge(nodes[1], nodes[2], weights(vec![0, 0]));
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_enable_limit(true);
+        mlc.set_start_node(0);
+        mlc.run().unwrap();
+        let frontiers = mlc.limits().frontiers();
+        assert_eq!(frontiers["shop"][0].values, vec![1, 1]);
+
+        // the seed is the value of the only path, so the label at node 1 is pruned before it
+        // reaches the shop and the correct seed is reported as unverified
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_enable_limit(true);
+        mlc.seed_limits_from(&frontiers, &[0, 0]).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        assert_eq!(values(&bags[&1]), vec![vec![1, 1]]);
+        assert!(!bags.contains_key(&2));
+        assert_eq!(mlc.statistics().limit_discards, 1);
+        assert_eq!(mlc.unverified_seeds(), vec![("shop", vec![1, 1])]);
+    }
+
+    fn values(bag: &Bag<usize>) -> Vec<Vec<u64>> {
+        let mut values = bag
+            .labels
+            .iter()
+            .map(|label| label.values.clone())
+            .collect::<Vec<_>>();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn test_filters() {
+        let g = read::read_graph_with_int_ids("testdata/edges_attributes.csv").unwrap();
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        assert_eq!(values(&bags[&1]), vec![vec![0, 5], vec![1, 1]]);
+        assert_eq!(values(&bags[&2]), vec![vec![1, 6], vec![2, 2], vec![5, 0]]);
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_edge_attribute_filter(0, 2);
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        assert_eq!(values(&bags[&1]), vec![vec![1, 1]]);
+        assert_eq!(values(&bags[&2]), vec![vec![2, 2]]);
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_edge_attribute_filter(1, 0);
+        mlc.set_node_filter(Box::new(|node_id| node_id != 1));
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        assert!(!bags.contains_key(&1));
+        assert_eq!(values(&bags[&2]), vec![vec![5, 0]]);
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_edge_filter(Box::new(|_, weight| weight.weights[1] < 5));
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        assert_eq!(values(&bags[&1]), vec![vec![1, 1]]);
+        assert_eq!(values(&bags[&2]), vec![vec![2, 2], vec![5, 0]]);
+    }
+
+    #[test]
+    fn test_automaton() {
+        let g = read::read_graph_with_int_ids("testdata/edges_modes.csv").unwrap();
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        assert_eq!(values(&bags[&3]), vec![vec![1, 1]]);
+
+        // walk (0), then transit (1), then walk, using transit at least once
+        let mut automaton = Automaton::new(3);
+        automaton.add_transition(0, 0, 0);
+        automaton.add_transition(0, 1, 1);
+        automaton.add_transition(1, 1, 1);
+        automaton.add_transition(1, 0, 2);
+        automaton.add_transition(2, 0, 2);
+        automaton.set_accepting(1);
+        automaton.set_accepting(2);
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_automaton(automaton);
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        let mut states = bags[&3]
+            .labels
+            .iter()
+            .map(|label| (label.state, label.values.clone(), label.path.clone()))
+            .collect::<Vec<_>>();
+        states.sort();
+        assert_eq!(
+            states,
+            vec![
+                (0, vec![1, 1], vec![0, 1, 3]),
+                (1, vec![4, 4], vec![0, 3]),
+                (2, vec![3, 3], vec![0, 1, 2, 3]),
+            ]
+        );
+
+        let accepted = mlc.accepted_bags();
+        assert_eq!(values(&accepted[&3]), vec![vec![3, 3]]);
+        assert_eq!(values(&accepted[&2]), vec![vec![2, 2]]);
+        assert!(!accepted.contains_key(&0));
+        assert!(!accepted.contains_key(&1));
+    }
+
+    #[test]
+    fn test_transfers() {
+        let g = read::read_graph_with_int_ids("testdata/edges_transfers.csv").unwrap();
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        // no transfer penalty when leaving the start; the label of line 1 is kept during the
+        // search, as it can stay on its line, but it is dominated in the result
+        assert_eq!(values(&bags[&1]), vec![vec![0, 1]]);
+        // staying on line 1 costs neither the waiting cost nor the transfer penalty, changing
+        // from line 2 costs both
+        let labels = bags[&2].labels.iter().collect::<Vec<_>>();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].values, vec![2, 2]);
+        assert_eq!(labels[0].line, Some(1));
+    }
+
+    #[test]
+    fn test_turn_costs() {
+        let g = read::read_graph_with_int_ids("testdata/edges_turns.csv").unwrap();
+
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        assert_eq!(values(&bags[&2]), vec![vec![2, 2]]);
+
+        let turn_costs = mlc::turns::read_turn_costs("testdata/turns.csv", &g, None).unwrap();
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_turn_costs(turn_costs).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        // the label over 3 is dominated at node 1, but not by the label of the turning edge
+        assert_eq!(values(&bags[&1]), vec![vec![1, 1]]);
+        assert_eq!(values(&bags[&2]), vec![vec![3, 3], vec![7, 2]]);
+
+        let mut turn_costs = mlc::turns::TurnCosts::new();
+        turn_costs.insert(EdgeIndex::new(0), EdgeIndex::new(2), vec![1, 1]);
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        assert!(matches!(
+            mlc.set_turn_costs(turn_costs),
+            Err(mlc::MLCError::InvalidTurnCost(_))
+        ));
+    }
+
+    #[test]
+    fn test_translate_bags() {
+        let g = read::read_graph_with_int_ids("testdata/edges.csv").unwrap();
+        let mut mlc = mlc::MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap().clone();
+        assert!(matches!(
+            mlc.translate_bags(&bags),
+            Err(mlc::MLCError::NodeMapNotSet)
+        ));
+
+        let mut node_map = ["a", "b", "c", "d"]
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.to_string(), id))
+            .collect::<BiMap<_, _>>();
+        assert!(matches!(
+            mlc::translate_bags(&bags, &node_map),
+            Err(mlc::MLCError::UnknownNodeId(4))
+        ));
+        node_map.insert("e".to_string(), 4);
+        mlc.set_node_map(node_map.clone());
+        let translated = mlc.translate_bags(&bags).unwrap();
+        assert_eq!(translated, mlc::translate_bags(&bags, &node_map).unwrap());
+        assert_eq!(translated.len(), 5);
+        assert!(translated["c"]
+            .labels
+            .iter()
+            .all(|label| label.node_id == "c" && label.path == ["a", "b", "c"]));
+
+        let path =
+            std::env::temp_dir().join(format!("mlc_test_translated_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        mlc::write_translated_bags(&bags, &node_map, path).unwrap();
+        let written = std::fs::read_to_string(path).unwrap();
+        assert!(written.starts_with("node_id|path|value_0|value_1\n"));
+        assert!(written.contains("\nc|a,b,c|0|3\n"));
+        std::fs::remove_file(path).unwrap();
+    }
 }
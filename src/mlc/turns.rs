@@ -0,0 +1,119 @@
+mod test;
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use bimap::BiMap;
+use petgraph::graph::EdgeIndex;
+
+use crate::bag::{NodeId, Weight, Weights};
+use crate::compression;
+use crate::graph::SearchGraph;
+
+/// Costs of pairs of consecutive edges, e.g. of turning at an intersection or of a specific
+/// transfer, added to a label when it enters the second edge from the first one (see
+/// `MLC::set_turn_costs`). They are added on top of the transfer penalty and waiting cost of
+/// the second edge.
+#[derive(Debug, Clone, Default)]
+pub struct TurnCosts {
+    costs: HashMap<(EdgeIndex, EdgeIndex), Vec<Weight>>,
+    // the edges that at least one turn starts at
+    from_edges: HashSet<EdgeIndex>,
+}
+
+impl TurnCosts {
+    pub fn new() -> TurnCosts {
+        TurnCosts::default()
+    }
+
+    /// Sets the cost of entering the edge `to` from the edge `from`, replacing an existing cost
+    /// of the same pair.
+    pub fn insert(&mut self, from: EdgeIndex, to: EdgeIndex, weights: Vec<Weight>) {
+        self.costs.insert((from, to), weights);
+        self.from_edges.insert(from);
+    }
+
+    /// Returns the cost of entering the edge `to` from the edge `from`, if there is one.
+    pub fn get(&self, from: EdgeIndex, to: EdgeIndex) -> Option<&[Weight]> {
+        self.costs
+            .get(&(from, to))
+            .map(|weights| weights.as_slice())
+    }
+
+    /// Returns true if a turn starts at the edge, i.e. if the costs after the edge depend on it.
+    pub fn starts_at(&self, edge: EdgeIndex) -> bool {
+        self.from_edges.contains(&edge)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.costs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EdgeIndex, EdgeIndex, &[Weight])> {
+        self.costs
+            .iter()
+            .map(|((from, to), weights)| (*from, *to, weights.as_slice()))
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Turn {
+    u: String,
+    v: String,
+    w: String,
+    weights: Weights,
+}
+
+/// Reads turn costs from a csv file with the columns `u,v,w,weights`, where a row is the cost of
+/// going from `u` over `v` to `w`. It applies to every pair of an edge from `u` to `v` and an
+/// edge from `v` to `w`. The weights have the same format as in the edges file, see
+/// `read::read_graph_and_reset_ids`.
+///
+/// Nodes are looked up in the node map if one is given, e.g. as returned by
+/// `read_graph_and_reset_ids`, otherwise they must be integer node ids. The file may be
+/// compressed with gzip or zstd, see `compression::open`.
+pub fn read_turn_costs<T, G: SearchGraph<T>>(
+    path: &str,
+    g: &G,
+    node_map: Option<&BiMap<String, usize>>,
+) -> Result<TurnCosts, Box<dyn Error>> {
+    let node_id = |node: &str| -> Result<NodeId, Box<dyn Error>> {
+        let node_id = match node_map {
+            Some(node_map) => node_map.get_by_left(node).copied(),
+            None => node.parse().ok(),
+        };
+        match node_id {
+            Some(node_id) if node_id < g.node_count() => Ok(node_id),
+            _ => Err(format!("{}: unknown node {:?}", path, node).into()),
+        }
+    };
+    let edges_between = |u: NodeId, v: NodeId| {
+        g.out_edges(u)
+            .filter(|edge| edge.target == v)
+            .map(|edge| edge.id)
+            .collect::<Vec<_>>()
+    };
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .quote(b'"')
+        .from_reader(compression::open(path)?);
+    let mut turn_costs = TurnCosts::new();
+    for result in rdr.deserialize() {
+        let turn: Turn = result?;
+        let (u, v, w) = (node_id(&turn.u)?, node_id(&turn.v)?, node_id(&turn.w)?);
+        let (from_edges, to_edges) = (edges_between(u, v), edges_between(v, w));
+        if from_edges.is_empty() || to_edges.is_empty() {
+            return Err(format!(
+                "{}: no edges from {} over {} to {}",
+                path, turn.u, turn.v, turn.w
+            )
+            .into());
+        }
+        for from in from_edges.iter() {
+            for to in to_edges.iter() {
+                turn_costs.insert(*from, *to, turn.weights.0.clone());
+            }
+        }
+    }
+    Ok(turn_costs)
+}
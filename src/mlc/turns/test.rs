@@ -0,0 +1,29 @@
+#[cfg(test)]
+mod tests {
+    use bimap::BiMap;
+    use petgraph::graph::EdgeIndex;
+
+    use super::super::*;
+    use crate::read;
+
+    #[test]
+    fn test_read_turn_costs() {
+        let g = read::read_graph_with_int_ids("testdata/edges_turns.csv").unwrap();
+        let turn_costs = read_turn_costs("testdata/turns.csv", &g, None).unwrap();
+        assert_eq!(turn_costs.iter().count(), 1);
+        assert_eq!(
+            turn_costs.get(EdgeIndex::new(0), EdgeIndex::new(1)),
+            Some(&[5, 0][..])
+        );
+        assert_eq!(turn_costs.get(EdgeIndex::new(3), EdgeIndex::new(1)), None);
+        assert!(turn_costs.starts_at(EdgeIndex::new(0)));
+        assert!(!turn_costs.starts_at(EdgeIndex::new(3)));
+
+        // external ids are looked up in the node map, which does not know node 2
+        let node_map = [("a", 0), ("b", 1), ("d", 3)]
+            .into_iter()
+            .map(|(name, id)| (name.to_string(), id))
+            .collect::<BiMap<_, _>>();
+        assert!(read_turn_costs("testdata/turns.csv", &g, Some(&node_map)).is_err());
+    }
+}
@@ -1,42 +1,71 @@
 mod test;
 
 use bimap::BiMap;
+use petgraph::visit::EdgeRef;
 use petgraph::Graph;
 use petgraph::{graph::NodeIndex, Directed};
 use serde::{de, Deserialize, Deserializer};
-use std::{error::Error, str::FromStr};
+use std::{error::Error, fmt, str::FromStr};
 
 use crate::bag::{NodeId, Weights, WeightsTuple};
+use crate::compression;
+use crate::graph::CsrGraph;
 
+// An edge of an edges file, with node names as read or as integer ids.
 #[derive(Debug, serde::Deserialize)]
-struct UntranslatedEdge {
-    u: String,
-    v: String,
-    weights: Weights,
-    hidden_weights: Option<Weights>,
+pub(crate) struct Edge<N> {
+    pub(crate) u: N,
+    pub(crate) v: N,
+    pub(crate) weights: Weights,
+    pub(crate) hidden_weights: Option<Weights>,
+    pub(crate) attributes: Option<u64>,
+    pub(crate) mode: Option<u32>,
+    pub(crate) line: Option<u32>,
+    pub(crate) transfer_weights: Option<Weights>,
+    pub(crate) wait_weights: Option<Weights>,
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct Edge {
-    u: NodeId,
-    v: NodeId,
-    weights: Weights,
-    hidden_weights: Option<Weights>,
+pub(crate) type UntranslatedEdge = Edge<String>;
+
+impl<N> Edge<N> {
+    pub(crate) fn weights_tuple(&self) -> WeightsTuple {
+        WeightsTuple {
+            weights: self.weights.clone().0,
+            hidden_weights: self.hidden_weights.clone().map(|w| w.0).unwrap_or(vec![]),
+            attributes: self.attributes.unwrap_or(0),
+            mode: self.mode.unwrap_or(0),
+            line: self.line.unwrap_or(0),
+            transfer_weights: self.transfer_weights.clone().map(|w| w.0).unwrap_or(vec![]),
+            wait_weights: self.wait_weights.clone().map(|w| w.0).unwrap_or(vec![]),
+        }
+    }
 }
 
 pub type MLCGraph<T> = Graph<Vec<T>, WeightsTuple, Directed>;
 
 // Reads a graph from a csv file. The csv file should have the following format:
-// u,v,weights,hidden_weights
+// u,v,weights,hidden_weights,attributes,mode,line,transfer_weights,wait_weights
 // where u and v are the node names, weights are the weights of the edge, and hidden_weights are the
-// hidden weights of the edge. The hidden_weights column is optional.
+// hidden weights of the edge. All columns but u, v and weights are optional.
+// The optional attributes column is an integer bitmask of edge attributes, see
+// MLC::set_edge_attribute_filter.
+// The optional mode column is an integer label of the edge read by the automaton of
+// regular-language constrained queries, see MLC::set_automaton.
+// The optional line column is an integer line of the edge, e.g. a transit route. The optional
+// transfer_weights and wait_weights columns are added to the weights when the edge is entered from
+// an edge of another line, where wait_weights is the cost of waiting at u for the line. Both have
+// the same length as weights. Costs of pairs of edges, e.g. turns, are read by
+// mlc::turns::read_turn_costs.
 // The node names can be any string, but they must be unique.
-// The weights and hidden_weights columns must be a comma-separated list of integers.
+// All weight columns are lists of integers separated by semicolons in parentheses, e.g. (1;2).
+// The file may be compressed with gzip or zstd, see compression::open.
+#[allow(clippy::type_complexity)]
 pub fn read_graph_and_reset_ids(
     path: &str,
 ) -> Result<(MLCGraph<()>, BiMap<String, usize>), Box<dyn Error>> {
-    // let mut rdr = csv::Reader::from_path(path)?;
-    let mut rdr = csv::ReaderBuilder::new().quote(b'"').from_path(path)?;
+    let mut rdr = csv::ReaderBuilder::new()
+        .quote(b'"')
+        .from_reader(compression::open(path)?);
 
     let mut edges = Vec::new();
     for result in rdr.deserialize() {
@@ -56,59 +85,150 @@ pub fn read_graph_and_reset_ids(
             node_count += 1;
         }
     }
-    let translated_edges = edges
-        .iter()
-        .map(|e| Edge {
-            u: *node_map.get_by_left(&e.u).unwrap(),
-            v: *node_map.get_by_left(&e.v).unwrap(),
-            weights: e.weights.clone(),
-            hidden_weights: e.hidden_weights.clone(),
-        })
-        .collect::<Vec<_>>();
-
-    let g =
-        Graph::<Vec<()>, WeightsTuple, Directed>::from_edges(translated_edges.iter().map(|e| {
-            (
-                NodeIndex::new(e.u),
-                NodeIndex::new(e.v),
-                WeightsTuple {
-                    weights: e.weights.clone().0,
-                    hidden_weights: e.hidden_weights.clone().map(|w| w.0).unwrap_or(vec![]),
-                },
-            )
-        }));
+    let g = Graph::<Vec<()>, WeightsTuple, Directed>::from_edges(edges.iter().map(|e| {
+        (
+            NodeIndex::new(*node_map.get_by_left(&e.u).unwrap()),
+            NodeIndex::new(*node_map.get_by_left(&e.v).unwrap()),
+            e.weights_tuple(),
+        )
+    }));
     Ok((g, node_map))
 }
 
 // Like read_graph_unresetted, but the node ids must be integers from 0 to n-1, where n is the
 // number of nodes in the graph. This function is faster than read_graph_unresetted.
 pub fn read_graph_with_int_ids(path: &str) -> Result<MLCGraph<()>, Box<dyn Error>> {
-    // let mut rdr = csv::Reader::from_path(path)?;
-    let mut rdr = csv::ReaderBuilder::new().quote(b'"').from_path(path)?;
+    let mut rdr = csv::ReaderBuilder::new()
+        .quote(b'"')
+        .from_reader(compression::open(path)?);
 
     let mut edges = Vec::new();
     for result in rdr.deserialize() {
-        let edge: Edge = result?;
+        let edge: Edge<NodeId> = result?;
         edges.push(edge);
     }
 
-    let g = Graph::<Vec<()>, WeightsTuple, Directed>::from_edges(edges.iter().map(|e| {
-        (
-            NodeIndex::new(e.u),
-            NodeIndex::new(e.v),
-            WeightsTuple {
-                weights: e.weights.clone().0,
-                hidden_weights: e.hidden_weights.clone().map(|w| w.0).unwrap_or(vec![]),
-            },
-        )
-    }));
+    let g = Graph::<Vec<()>, WeightsTuple, Directed>::from_edges(
+        edges
+            .iter()
+            .map(|e| (NodeIndex::new(e.u), NodeIndex::new(e.v), e.weights_tuple())),
+    );
     Ok(g)
 }
 
+// Like read_graph_with_int_ids, but reads the graph into a CsrGraph, which needs less memory
+// for large graphs. The number of nodes is the largest node id plus one.
+pub fn read_csr_graph_with_int_ids(path: &str) -> Result<CsrGraph<()>, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .quote(b'"')
+        .from_reader(compression::open(path)?);
+
+    let mut edges = Vec::new();
+    let mut node_count = 0;
+    for result in rdr.deserialize() {
+        let edge: Edge<NodeId> = result?;
+        node_count = node_count.max(edge.u + 1).max(edge.v + 1);
+        edges.push((edge.u, edge.v, edge.weights_tuple()));
+    }
+
+    CsrGraph::from_edges(vec![vec![]; node_count], edges)
+}
+
+// Writes the graph in the format read by read_graph_and_reset_ids, with the external node ids of
+// node_map, or in the format read by read_graph_with_int_ids if node_map is None. The optional
+// columns are only written if any edge uses them. The file is compressed by its extension, see
+// compression::create.
+pub fn write_graph<T>(
+    g: &MLCGraph<T>,
+    node_map: Option<&BiMap<String, usize>>,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let weights = g.edge_weights().collect::<Vec<_>>();
+    let has_hidden_weights = weights.iter().any(|w| !w.hidden_weights.is_empty());
+    let has_attributes = weights.iter().any(|w| w.attributes != 0);
+    let has_mode = weights.iter().any(|w| w.mode != 0);
+    let has_line = weights.iter().any(|w| w.line != 0);
+    let has_transfer_weights = weights.iter().any(|w| !w.transfer_weights.is_empty());
+    let has_wait_weights = weights.iter().any(|w| !w.wait_weights.is_empty());
+    let columns = [
+        ("hidden_weights", has_hidden_weights),
+        ("attributes", has_attributes),
+        ("mode", has_mode),
+        ("line", has_line),
+        ("transfer_weights", has_transfer_weights),
+        ("wait_weights", has_wait_weights),
+    ];
+
+    let mut wtr = csv::Writer::from_writer(compression::create(path)?);
+    let mut header = vec!["u", "v", "weights"];
+    header.extend(
+        columns
+            .iter()
+            .filter(|(_, used)| *used)
+            .map(|(name, _)| *name),
+    );
+    wtr.write_record(&header)?;
+
+    let node_id = |node: NodeIndex| -> Result<String, Box<dyn Error>> {
+        match node_map {
+            Some(node_map) => Ok(node_map
+                .get_by_right(&node.index())
+                .ok_or(format!("Node {} is not in the node map", node.index()))?
+                .clone()),
+            None => Ok(node.index().to_string()),
+        }
+    };
+    // empty weights are written as an empty field, which is read as a missing value
+    let format_weights = |weights: &[u64]| {
+        if weights.is_empty() {
+            String::new()
+        } else {
+            Weights(weights.to_vec()).to_string()
+        }
+    };
+    for edge in g.edge_references() {
+        let w = edge.weight();
+        let mut record = vec![
+            node_id(edge.source())?,
+            node_id(edge.target())?,
+            Weights(w.weights.clone()).to_string(),
+        ];
+        let values = [
+            format_weights(&w.hidden_weights),
+            w.attributes.to_string(),
+            w.mode.to_string(),
+            w.line.to_string(),
+            format_weights(&w.transfer_weights),
+            format_weights(&w.wait_weights),
+        ];
+        record.extend(
+            values
+                .into_iter()
+                .zip(columns.iter())
+                .filter(|(_, (_, used))| *used)
+                .map(|(value, _)| value),
+        );
+        wtr.write_record(&record)?;
+    }
+    wtr.into_inner().map_err(|err| err.into_error())?.finish()?;
+    Ok(())
+}
+
+impl fmt::Display for Weights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values = self
+            .0
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "({})", values.join(";"))
+    }
+}
+
 impl FromStr for Weights {
     type Err = std::num::ParseIntError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = &s[1..s.len() - 1];
+        let s = s.get(1..s.len().saturating_sub(1)).unwrap_or("");
         let values = s
             .split(';')
             .map(|s| s.parse::<u64>())
@@ -0,0 +1,128 @@
+mod test;
+
+use std::error::Error;
+use std::fmt::{Debug, Write as _};
+use std::process::Command;
+
+use bimap::BiMap;
+
+use crate::bag::{Bag, NodeId, Weights};
+use crate::graph::SearchGraph;
+
+// colors of the highlighted labels, cycled if a bag has more labels
+const COLORS: [&str; 8] = [
+    "#e41a1c", "#377eb8", "#4daf4a", "#984ea3", "#ff7f00", "#a65628", "#f781bf", "#999999",
+];
+
+/// Renders a graph in the DOT format, for debugging small graphs.
+///
+/// Nodes are named by their external ids from `node_map`, or by their index if it is `None`, and
+/// show their categories. Edges show their weights, and their hidden weights, mode and line if
+/// set. If `highlight` is given, the edges of the graph are drawn in gray, and the path of each
+/// label of the bag is drawn on top in its own color, labeled with its values at the last edge.
+/// The labels are ordered by their values, so the colors are stable between runs.
+pub fn render_dot<T: Debug, G: SearchGraph<T>>(
+    g: &G,
+    node_map: Option<&BiMap<String, usize>>,
+    highlight: Option<&Bag<NodeId>>,
+) -> String {
+    let name = |node: NodeId| match node_map.and_then(|node_map| node_map.get_by_right(&node)) {
+        Some(id) => escape(id),
+        None => node.to_string(),
+    };
+
+    let mut dot = String::from("digraph {\n    node [shape=circle];\n");
+    for node in 0..g.node_count() {
+        let mut label = name(node);
+        let categories = g.categories(node).unwrap_or(&[]);
+        if !categories.is_empty() {
+            let categories = categories
+                .iter()
+                .map(|category| escape(&format!("{:?}", category)))
+                .collect::<Vec<_>>();
+            label = format!("{}\\n{}", label, categories.join(", "));
+        }
+        let _ = writeln!(dot, "    {} [label=\"{}\"];", node, label);
+    }
+
+    let edge_color = if highlight.is_some() {
+        ", color=gray, fontcolor=gray"
+    } else {
+        ""
+    };
+    for node in 0..g.node_count() {
+        for edge in g.out_edges(node) {
+            let weights = edge.weights;
+            let mut label = Weights(weights.weights.to_vec()).to_string();
+            if !weights.hidden_weights.is_empty() {
+                let hidden_weights = Weights(weights.hidden_weights.to_vec());
+                let _ = write!(label, " hidden {}", hidden_weights);
+            }
+            if weights.mode != 0 {
+                let _ = write!(label, " mode {}", weights.mode);
+            }
+            if weights.line != 0 {
+                let _ = write!(label, " line {}", weights.line);
+            }
+            let _ = writeln!(
+                dot,
+                "    {} -> {} [label=\"{}\"{}];",
+                node, edge.target, label, edge_color
+            );
+        }
+    }
+
+    if let Some(bag) = highlight {
+        let mut labels = bag.labels.iter().collect::<Vec<_>>();
+        labels.sort_by(|a, b| (&a.values, &a.path).cmp(&(&b.values, &b.path)));
+        if let Some(label) = labels.first() {
+            let _ = writeln!(dot, "    {} [penwidth=3];", label.node_id);
+        }
+        for (rank, label) in labels.iter().enumerate() {
+            let color = COLORS[rank % COLORS.len()];
+            let steps = label.path.windows(2).collect::<Vec<_>>();
+            for (i, step) in steps.iter().enumerate() {
+                let values = if i + 1 == steps.len() {
+                    format!(
+                        ", label=\"{}\", fontcolor=\"{}\"",
+                        Weights(label.values.clone()),
+                        color
+                    )
+                } else {
+                    String::new()
+                };
+                let _ = writeln!(
+                    dot,
+                    "    {} -> {} [color=\"{}\", penwidth=2{}];",
+                    step[0], step[1], color, values
+                );
+            }
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Writes the DOT output of `render_dot` to `path`. If `path` ends with `.svg`, the output is
+/// converted with the `dot` program of Graphviz, which must be installed.
+pub fn write_rendering(dot: &str, path: &str) -> Result<(), Box<dyn Error>> {
+    if !path.ends_with(".svg") {
+        std::fs::write(path, dot)?;
+        return Ok(());
+    }
+    let mut child = Command::new("dot")
+        .args(["-Tsvg", "-o", path])
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Could not run dot, is Graphviz installed? {}", err))?;
+    std::io::Write::write_all(&mut child.stdin.take().unwrap(), dot.as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(format!("dot failed with {}", status).into());
+    }
+    Ok(())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use bimap::BiMap;
+
+    use crate::bag::WeightsTuple;
+    use crate::mlc::MLC;
+    use crate::read;
+    use crate::read::MLCGraph;
+    use crate::render::*;
+
+    #[test]
+    fn test_render_dot() {
+        let mut g = MLCGraph::new();
+        let a = g.add_node(vec!["shop"]);
+        let b = g.add_node(vec![]);
+        g.add_edge(
+            a,
+            b,
+            WeightsTuple {
+                weights: vec![1, 2],
+                hidden_weights: vec![3],
+                mode: 4,
+                ..Default::default()
+            },
+        );
+        let node_map = BiMap::from_iter([("a \"x\"".to_string(), 0), ("b".to_string(), 1)]);
+        let dot = render_dot(&g, Some(&node_map), None);
+        assert!(dot.contains("0 [label=\"a \\\"x\\\"\\n\\\"shop\\\"\"];"));
+        assert!(dot.contains("1 [label=\"b\"];"));
+        assert!(dot.contains("0 -> 1 [label=\"(1;2) hidden (3) mode 4\"];"));
+    }
+
+    #[test]
+    fn test_render_highlighted_paths() {
+        let g = read::read_graph_with_int_ids("testdata/edges.csv").unwrap();
+        let mut mlc = MLC::new(&g).unwrap();
+        mlc.set_start_node(0);
+        let bags = mlc.run().unwrap();
+        let dot = render_dot(&g, None, Some(&bags[&2]));
+        assert!(dot.contains("0 -> 1 [label=\"(0;1)\", color=gray, fontcolor=gray];"));
+        assert!(dot.contains("2 [penwidth=3];"));
+        // the labels (0;3) to (3;0), each with its own color
+        assert!(dot.contains("0 -> 1 [color=\"#e41a1c\", penwidth=2];"));
+        assert!(dot.contains(
+            "1 -> 2 [color=\"#e41a1c\", penwidth=2, label=\"(0;3)\", fontcolor=\"#e41a1c\"];"
+        ));
+        assert!(dot.contains(
+            "1 -> 2 [color=\"#984ea3\", penwidth=2, label=\"(3;0)\", fontcolor=\"#984ea3\"];"
+        ));
+        assert!(dot.ends_with("}\n"));
+
+        let path = std::env::temp_dir().join(format!("mlc_test_render_{}.dot", std::process::id()));
+        let path = path.to_str().unwrap();
+        write_rendering(&dot, path).unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), dot);
+        std::fs::remove_file(path).unwrap();
+    }
+}
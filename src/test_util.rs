@@ -0,0 +1,15 @@
+//! Helpers shared by the tests.
+
+/// A simple linear congruential generator to stay deterministic without dependencies.
+pub struct Lcg(pub u64);
+
+impl Lcg {
+    /// Returns the next number in `0..modulus`.
+    pub fn next(&mut self, modulus: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % modulus
+    }
+}
@@ -0,0 +1,502 @@
+mod test;
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::NodeIndex;
+use petgraph::unionfind::UnionFind;
+use petgraph::{Directed, Graph};
+
+use crate::bag::{NodeId, Weight, WeightsTuple};
+use crate::compression;
+use crate::graph::SearchGraph;
+use crate::read::UntranslatedEdge;
+
+/// What is wrong with an edge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    /// The row could not be parsed. It is not part of the graph.
+    InvalidRow(String),
+    InconsistentWeights {
+        expected: usize,
+        found: usize,
+    },
+    InconsistentHiddenWeights {
+        expected: usize,
+        found: usize,
+    },
+    InconsistentTransferWeights {
+        expected: usize,
+        found: usize,
+    },
+    InconsistentWaitWeights {
+        expected: usize,
+        found: usize,
+    },
+    /// The edge lies on a cycle of edges whose weights are all zero.
+    ZeroWeightCycle,
+    /// A parallel edge with the same attributes, mode, line, transfer and wait weights has at
+    /// most the same weights, so this edge never yields a label that is not dominated.
+    ParallelDominated {
+        by_edge: usize,
+        by_input_line: Option<u64>,
+    },
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IssueKind::InvalidRow(reason) => write!(f, "invalid row: {}", reason),
+            IssueKind::InconsistentWeights { expected, found } => {
+                write!(f, "expected {} weights, found {}", expected, found)
+            }
+            IssueKind::InconsistentHiddenWeights { expected, found } => {
+                write!(f, "expected {} hidden weights, found {}", expected, found)
+            }
+            IssueKind::InconsistentTransferWeights { expected, found } => {
+                write!(
+                    f,
+                    "expected 0 or {} transfer weights, found {}",
+                    expected, found
+                )
+            }
+            IssueKind::InconsistentWaitWeights { expected, found } => {
+                write!(
+                    f,
+                    "expected 0 or {} wait weights, found {}",
+                    expected, found
+                )
+            }
+            IssueKind::ZeroWeightCycle => write!(f, "part of a cycle with zero weights"),
+            IssueKind::ParallelDominated {
+                by_edge,
+                by_input_line,
+            } => match by_input_line {
+                Some(line) => write!(f, "dominated by the parallel edge in line {}", line),
+                None => write!(f, "dominated by the parallel edge {}", by_edge),
+            },
+        }
+    }
+}
+
+/// An offending edge. `edge` is the index of the edge in the graph and `input_line` the line it
+/// was read from, if known; both are `None` for rows that could not be parsed and were skipped,
+/// resp. for graphs that were not read from a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeIssue {
+    pub edge: Option<usize>,
+    pub input_line: Option<u64>,
+    pub endpoints: Option<(String, String)>,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for EdgeIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.input_line, self.edge) {
+            (Some(line), _) => write!(f, "line {}", line)?,
+            (None, Some(edge)) => write!(f, "edge {}", edge)?,
+            (None, None) => write!(f, "unknown edge")?,
+        }
+        if let Some((u, v)) = &self.endpoints {
+            write!(f, " ({} -> {})", u, v)?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+/// The result of validating a graph, see `validate_file` and `validate_graph`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// Number of weights and hidden weights per edge, taken from the first edge.
+    pub weight_arity: usize,
+    pub hidden_weight_arity: usize,
+    /// Minimum and maximum of each weight and hidden weight over the edges with the expected
+    /// number of weights.
+    pub weight_ranges: Vec<(Weight, Weight)>,
+    pub hidden_weight_ranges: Vec<(Weight, Weight)>,
+    /// Sizes of the weakly connected components, largest first.
+    pub component_sizes: Vec<usize>,
+    /// Nodes without any edge.
+    pub isolated_nodes: Vec<String>,
+    /// Integer ids below the largest id without any edge, for which `read_graph_with_int_ids`
+    /// creates isolated nodes. Only set by `validate_file` if all ids are integers.
+    pub missing_int_ids: Vec<usize>,
+    /// Nodes that can not be reached from the start node, if one was given.
+    pub unreachable_nodes: Option<Vec<String>>,
+    pub edge_issues: Vec<EdgeIssue>,
+}
+
+impl Report {
+    /// Returns true if anything worth a warning was found.
+    pub fn has_issues(&self) -> bool {
+        !self.edge_issues.is_empty()
+            || !self.isolated_nodes.is_empty()
+            || !self.missing_int_ids.is_empty()
+            || self
+                .unreachable_nodes
+                .as_ref()
+                .is_some_and(|nodes| !nodes.is_empty())
+    }
+}
+
+// number of listed nodes before the output is shortened
+const MAX_LISTED_NODES: usize = 10;
+
+fn write_nodes<T: fmt::Display>(f: &mut fmt::Formatter, name: &str, nodes: &[T]) -> fmt::Result {
+    write!(f, "{}: {}", name, nodes.len())?;
+    if !nodes.is_empty() {
+        let listed = nodes
+            .iter()
+            .take(MAX_LISTED_NODES)
+            .map(|node| node.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let more = if nodes.len() > MAX_LISTED_NODES {
+            ", ..."
+        } else {
+            ""
+        };
+        write!(f, " ({}{})", listed, more)?;
+    }
+    writeln!(f)
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "nodes: {}", self.node_count)?;
+        writeln!(f, "edges: {}", self.edge_count)?;
+        writeln!(
+            f,
+            "weights per edge: {}, hidden weights per edge: {}",
+            self.weight_arity, self.hidden_weight_arity
+        )?;
+        for (i, (min, max)) in self.weight_ranges.iter().enumerate() {
+            writeln!(f, "weight {}: {}..={}", i, min, max)?;
+        }
+        for (i, (min, max)) in self.hidden_weight_ranges.iter().enumerate() {
+            writeln!(f, "hidden weight {}: {}..={}", i, min, max)?;
+        }
+        writeln!(
+            f,
+            "weakly connected components: {} (largest: {} nodes)",
+            self.component_sizes.len(),
+            self.component_sizes.first().unwrap_or(&0)
+        )?;
+        write_nodes(f, "isolated nodes", &self.isolated_nodes)?;
+        write_nodes(f, "missing int ids", &self.missing_int_ids)?;
+        if let Some(unreachable_nodes) = &self.unreachable_nodes {
+            write_nodes(f, "unreachable nodes", unreachable_nodes)?;
+        }
+        writeln!(f, "edge issues: {}", self.edge_issues.len())?;
+        for issue in self.edge_issues.iter() {
+            writeln!(f, "  {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+struct InputEdge {
+    id: usize,
+    input_line: Option<u64>,
+    u: NodeId,
+    v: NodeId,
+    weights: WeightsTuple,
+}
+
+/// Validates the edges csv file at `path` (see `read::read_graph_and_reset_ids` for the format)
+/// without stopping at the first error. Rows that can not be parsed are reported and skipped.
+///
+/// If `start` is given, the nodes that can not be reached from it are reported as well.
+pub fn validate_file(path: &str, start: Option<&str>) -> Result<Report, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .quote(b'"')
+        .from_reader(compression::open(path)?);
+    let headers = rdr.headers()?.clone();
+
+    let mut issues = vec![];
+    let mut rows = vec![];
+    for result in rdr.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(err) => {
+                issues.push(EdgeIssue {
+                    edge: None,
+                    input_line: err.position().map(|position| position.line()),
+                    endpoints: None,
+                    kind: IssueKind::InvalidRow(err.to_string()),
+                });
+                continue;
+            }
+        };
+        let input_line = record.position().map(|position| position.line());
+        match record.deserialize::<UntranslatedEdge>(Some(&headers)) {
+            Ok(edge) => rows.push((input_line, edge)),
+            Err(err) => issues.push(EdgeIssue {
+                edge: None,
+                input_line,
+                endpoints: None,
+                kind: IssueKind::InvalidRow(err.to_string()),
+            }),
+        }
+    }
+
+    let int_ids = rows
+        .iter()
+        .map(|(_, edge)| Some((edge.u.parse::<usize>().ok()?, edge.v.parse::<usize>().ok()?)))
+        .collect::<Option<Vec<_>>>();
+    let mut node_names = vec![];
+    let mut missing_int_ids = vec![];
+    let endpoints = match int_ids {
+        // like read_graph_with_int_ids, every id up to the largest one is a node
+        Some(int_ids) if !int_ids.is_empty() => {
+            let node_count = int_ids.iter().map(|(u, v)| u.max(v) + 1).max().unwrap();
+            let mut has_edge = vec![false; node_count];
+            for (u, v) in int_ids.iter() {
+                has_edge[*u] = true;
+                has_edge[*v] = true;
+            }
+            missing_int_ids = (0..node_count).filter(|id| !has_edge[*id]).collect();
+            node_names = (0..node_count).map(|id| id.to_string()).collect();
+            int_ids
+        }
+        _ => {
+            let mut ids = HashMap::new();
+            let mut id = |name: &String| {
+                *ids.entry(name.clone()).or_insert_with(|| {
+                    node_names.push(name.clone());
+                    node_names.len() - 1
+                })
+            };
+            rows.iter()
+                .map(|(_, edge)| (id(&edge.u), id(&edge.v)))
+                .collect()
+        }
+    };
+
+    let edges = rows
+        .iter()
+        .zip(endpoints)
+        .enumerate()
+        .map(|(id, ((input_line, edge), (u, v)))| InputEdge {
+            id,
+            input_line: *input_line,
+            u,
+            v,
+            weights: edge.weights_tuple(),
+        })
+        .collect();
+
+    let start = match start {
+        Some(start) => Some(
+            node_names
+                .iter()
+                .position(|name| name == start)
+                .ok_or(format!("Start node not found: {}", start))?,
+        ),
+        None => None,
+    };
+
+    let mut report = build_report(node_names, edges, issues, start);
+    // nodes of missing ids are reported as such
+    report.isolated_nodes.clear();
+    report.missing_int_ids = missing_int_ids;
+    Ok(report)
+}
+
+/// Validates a graph that is already in memory. Edges are identified by their index.
+pub fn validate_graph<T, G: SearchGraph<T>>(g: &G, start: Option<NodeId>) -> Report {
+    let mut edges = vec![];
+    for node in 0..g.node_count() {
+        for edge in g.out_edges(node) {
+            edges.push(InputEdge {
+                id: edge.id.index(),
+                input_line: None,
+                u: node,
+                v: edge.target,
+                weights: edge.weights.to_weights_tuple(),
+            });
+        }
+    }
+    edges.sort_by_key(|edge| edge.id);
+    let node_names = (0..g.node_count()).map(|id| id.to_string()).collect();
+    build_report(node_names, edges, vec![], start)
+}
+
+fn build_report(
+    node_names: Vec<String>,
+    edges: Vec<InputEdge>,
+    mut issues: Vec<EdgeIssue>,
+    start: Option<NodeId>,
+) -> Report {
+    let node_count = node_names.len();
+    let issue = |edge: &InputEdge, kind: IssueKind| EdgeIssue {
+        edge: Some(edge.id),
+        input_line: edge.input_line,
+        endpoints: Some((node_names[edge.u].clone(), node_names[edge.v].clone())),
+        kind,
+    };
+
+    let weight_arity = edges.first().map_or(0, |e| e.weights.weights.len());
+    let hidden_weight_arity = edges.first().map_or(0, |e| e.weights.hidden_weights.len());
+    let mut weight_ranges = vec![(Weight::MAX, 0); weight_arity];
+    let mut hidden_weight_ranges = vec![(Weight::MAX, 0); hidden_weight_arity];
+    let mut consistent = vec![false; edges.len()];
+    for (i, edge) in edges.iter().enumerate() {
+        let weights = &edge.weights;
+        let kind = if weights.weights.len() != weight_arity {
+            Some(IssueKind::InconsistentWeights {
+                expected: weight_arity,
+                found: weights.weights.len(),
+            })
+        } else if weights.hidden_weights.len() != hidden_weight_arity {
+            Some(IssueKind::InconsistentHiddenWeights {
+                expected: hidden_weight_arity,
+                found: weights.hidden_weights.len(),
+            })
+        } else if ![0, weight_arity].contains(&weights.transfer_weights.len()) {
+            Some(IssueKind::InconsistentTransferWeights {
+                expected: weight_arity,
+                found: weights.transfer_weights.len(),
+            })
+        } else if ![0, weight_arity].contains(&weights.wait_weights.len()) {
+            Some(IssueKind::InconsistentWaitWeights {
+                expected: weight_arity,
+                found: weights.wait_weights.len(),
+            })
+        } else {
+            None
+        };
+        match kind {
+            Some(kind) => issues.push(issue(edge, kind)),
+            None => {
+                consistent[i] = true;
+                update_ranges(&mut weight_ranges, &weights.weights);
+                update_ranges(&mut hidden_weight_ranges, &weights.hidden_weights);
+            }
+        }
+    }
+    if !consistent.contains(&true) {
+        weight_ranges.clear();
+        hidden_weight_ranges.clear();
+    }
+
+    let mut components = UnionFind::<usize>::new(node_count);
+    let mut has_edge = vec![false; node_count];
+    let mut successors = vec![vec![]; node_count];
+    for edge in edges.iter() {
+        components.union(edge.u, edge.v);
+        has_edge[edge.u] = true;
+        has_edge[edge.v] = true;
+        successors[edge.u].push(edge.v);
+    }
+    let mut component_sizes = HashMap::new();
+    for node in 0..node_count {
+        *component_sizes.entry(components.find(node)).or_insert(0) += 1;
+    }
+    let mut component_sizes = component_sizes.into_values().collect::<Vec<_>>();
+    component_sizes.sort_by(|a, b| b.cmp(a));
+
+    let isolated_nodes = (0..node_count)
+        .filter(|node| !has_edge[*node])
+        .map(|node| node_names[node].clone())
+        .collect();
+
+    let unreachable_nodes = start.map(|start| {
+        let mut reached = vec![false; node_count];
+        let mut queue = VecDeque::from([start]);
+        reached[start] = true;
+        while let Some(node) = queue.pop_front() {
+            for successor in successors[node].iter() {
+                if !reached[*successor] {
+                    reached[*successor] = true;
+                    queue.push_back(*successor);
+                }
+            }
+        }
+        (0..node_count)
+            .filter(|node| !reached[*node])
+            .map(|node| node_names[node].clone())
+            .collect()
+    });
+
+    // edges with zero weights inside a strongly connected component of such edges
+    let mut zero_graph = Graph::<(), (), Directed>::with_capacity(node_count, 0);
+    for _ in 0..node_count {
+        zero_graph.add_node(());
+    }
+    let is_zero = |i: usize| consistent[i] && edges[i].weights.weights.iter().all(|w| *w == 0);
+    for (i, edge) in edges.iter().enumerate() {
+        if is_zero(i) {
+            zero_graph.add_edge(NodeIndex::new(edge.u), NodeIndex::new(edge.v), ());
+        }
+    }
+    let mut zero_component = vec![0; node_count];
+    for (component, nodes) in tarjan_scc(&zero_graph).iter().enumerate() {
+        for node in nodes {
+            zero_component[node.index()] = component;
+        }
+    }
+    for (i, edge) in edges.iter().enumerate() {
+        if is_zero(i) && zero_component[edge.u] == zero_component[edge.v] {
+            issues.push(issue(edge, IssueKind::ZeroWeightCycle));
+        }
+    }
+
+    let mut parallel_edges: HashMap<(NodeId, NodeId), Vec<usize>> = HashMap::new();
+    for (i, edge) in edges.iter().enumerate() {
+        if consistent[i] {
+            parallel_edges.entry((edge.u, edge.v)).or_default().push(i);
+        }
+    }
+    for group in parallel_edges.values() {
+        for &i in group.iter() {
+            let dominating = group.iter().find(|&&j| {
+                let (a, b) = (&edges[j].weights, &edges[i].weights);
+                j != i
+                    && a.attributes == b.attributes
+                    && a.mode == b.mode
+                    && a.line == b.line
+                    && a.transfer_weights == b.transfer_weights
+                    && a.wait_weights == b.wait_weights
+                    && a.weights.iter().zip(b.weights.iter()).all(|(a, b)| a <= b)
+                    // of equal edges, only the later ones are dominated
+                    && (a.weights != b.weights || j < i)
+            });
+            if let Some(&j) = dominating {
+                issues.push(issue(
+                    &edges[i],
+                    IssueKind::ParallelDominated {
+                        by_edge: edges[j].id,
+                        by_input_line: edges[j].input_line,
+                    },
+                ));
+            }
+        }
+    }
+    issues.sort_by_key(|issue| (issue.input_line, issue.edge));
+
+    Report {
+        node_count,
+        edge_count: edges.len(),
+        weight_arity,
+        hidden_weight_arity,
+        weight_ranges,
+        hidden_weight_ranges,
+        component_sizes,
+        isolated_nodes,
+        missing_int_ids: vec![],
+        unreachable_nodes,
+        edge_issues: issues,
+    }
+}
+
+fn update_ranges(ranges: &mut [(Weight, Weight)], weights: &[Weight]) {
+    for ((min, max), weight) in ranges.iter_mut().zip(weights.iter()) {
+        *min = (*min).min(*weight);
+        *max = (*max).max(*weight);
+    }
+}
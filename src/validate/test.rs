@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use crate::read;
+    use crate::validate::{validate_file, validate_graph, IssueKind};
+
+    #[test]
+    fn test_validate_file() {
+        let report = validate_file("testdata/edges_invalid.csv", Some("0")).unwrap();
+        assert_eq!(report.node_count, 7);
+        assert_eq!(report.edge_count, 6);
+        assert_eq!(report.weight_arity, 2);
+        assert_eq!(report.weight_ranges, vec![(0, 2), (0, 3)]);
+        assert_eq!(report.component_sizes, vec![4, 2, 1]);
+        assert_eq!(report.missing_int_ids, vec![4]);
+        assert_eq!(
+            report.unreachable_nodes,
+            Some(vec!["4".to_string(), "5".to_string(), "6".to_string()])
+        );
+
+        let issues = report
+            .edge_issues
+            .iter()
+            .map(|issue| (issue.input_line, issue.kind.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(issues[0], (Some(3), IssueKind::ZeroWeightCycle));
+        assert_eq!(issues[1], (Some(4), IssueKind::ZeroWeightCycle));
+        assert_eq!(
+            issues[2],
+            (
+                Some(5),
+                IssueKind::ParallelDominated {
+                    by_edge: 0,
+                    by_input_line: Some(2)
+                }
+            )
+        );
+        assert_eq!(
+            issues[3],
+            (
+                Some(6),
+                IssueKind::InconsistentWeights {
+                    expected: 2,
+                    found: 1
+                }
+            )
+        );
+        assert_eq!(issues[4].0, Some(7));
+        assert!(matches!(issues[4].1, IssueKind::InvalidRow(_)));
+        assert_eq!(issues.len(), 5);
+        assert!(report.has_issues());
+
+        assert!(validate_file("testdata/edges_invalid.csv", Some("42")).is_err());
+    }
+
+    #[test]
+    fn test_validate_graph() {
+        let g = read::read_graph_with_int_ids("testdata/edges_high_index.csv").unwrap();
+        let report = validate_graph(&g, None);
+        assert_eq!(report.node_count, 23);
+        assert_eq!(report.isolated_nodes.len(), 19);
+        assert!(report.missing_int_ids.is_empty());
+        assert_eq!(report.unreachable_nodes, None);
+
+        let g = read::read_graph_with_int_ids("testdata/edges.csv").unwrap();
+        let report = validate_graph(&g, Some(0));
+        assert!(!report.has_issues(), "{}", report);
+    }
+}